@@ -1,25 +1,109 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+use crate::application::preferences;
 
-pub struct ColorGenerator {}
+// Кривая, по которой анимация проходит путь от 0 до 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut
+}
 
-impl ColorGenerator {
-    pub fn get_milliseconds() -> u128 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis()
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => if t < 0.5 {
+                2.0 * t * t
+            } else {
+                1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+            }
+        }
+    }
+}
+
+thread_local! {
+    static CLOCK: Instant = Instant::now();
+    // До этого момента хотя бы один переход ещё идёт и окно нужно перерисовывать
+    static BUSY_UNTIL: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+fn scaled(duration_ms: u32) -> Duration {
+    Duration::from_millis((duration_ms as f32 / preferences::current().animation_speed) as u64)
+}
+
+// Однократный переход от 0 до 1. Задержка и длительность делятся на скорость анимаций из настроек
+#[derive(Copy, Clone, Debug)]
+pub struct Tween {
+    start: Instant,
+    duration: Duration,
+    easing: Easing
+}
+
+impl Tween {
+    pub fn start(duration_ms: u32, easing: Easing) -> Self {
+        Self::after(0, duration_ms, easing)
+    }
+
+    // Переход, который начнётся через delay_ms, например следом за другим
+    pub fn after(delay_ms: u32, duration_ms: u32, easing: Easing) -> Self {
+        // При уменьшенном движении переход сразу оказывается в конце
+        let (delay, duration) = if preferences::current().reduced_motion {
+            (Duration::ZERO, Duration::ZERO)
+        } else {
+            (scaled(delay_ms), scaled(duration_ms))
+        };
+        let tween = Tween { start: Instant::now() + delay, duration, easing };
+
+        BUSY_UNTIL.with(|busy| {
+            let end = tween.start + tween.duration;
+            busy.set(Some(busy.get().map_or(end, |until| until.max(end))));
+        });
+
+        tween
     }
 
-    pub fn get_color_component(offset: u32, duration: u32, bottom: u8, top: u8) -> u8 {
-        let m = (
-            ((Self::get_milliseconds() + offset as u128) % duration as u128) as i128 -
-                (duration as i128 / 2)
-        ).abs() as u32;
+    pub fn value(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+
+        let elapsed = Instant::now().saturating_duration_since(self.start);
 
-        let divider = duration / 256;
-        let range = top - bottom;
-        let value = ((m / divider) * range as u32) / 256;
+        self.easing.apply(elapsed.as_secs_f32() / self.duration.as_secs_f32())
+    }
 
-        (bottom + value as u8) as u8
+    pub fn is_finished(&self) -> bool {
+        Instant::now() >= self.start + self.duration
     }
-}
\ No newline at end of file
+}
+
+// Идёт ли сейчас хотя бы один переход
+pub fn is_running() -> bool {
+    BUSY_UNTIL.with(|busy| busy.get().is_some_and(|until| Instant::now() < until))
+}
+
+pub fn is_pulsing() -> bool {
+    !preferences::current().reduced_motion
+}
+
+// Бесконечное переливание 0 -> 1 -> 0 за период. Смещение разводит по фазе соседние клетки.
+// При уменьшенном движении всегда 1, то есть конечный цвет
+pub fn pulse(offset: u32, duration_ms: u32) -> f32 {
+    if !is_pulsing() {
+        return 1.0;
+    }
+
+    // Период не короче 256 мс, как и раньше
+    let period = scaled(duration_ms).as_millis().max(256);
+    let elapsed = CLOCK.with(|clock| clock.elapsed().as_millis()) + offset as u128;
+    let phase = (elapsed % period) as f32 / period as f32;
+
+    Easing::EaseInOut.apply(1.0 - (2.0 * phase - 1.0).abs())
+}
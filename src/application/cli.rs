@@ -0,0 +1,400 @@
+use std::path::PathBuf;
+use crate::application::{dictionary, lang, leaders, save, settings, solver, storage, verify};
+use crate::application::{app, load_config};
+use crate::field::*;
+
+pub enum Command {
+    Play,
+    New {
+        seed: Option<u64>,
+        size: Option<(i32, i32)>,
+        out: Option<PathBuf>,
+        force: bool
+    },
+    Solve(PathBuf),
+    Check(String),
+    Leaders,
+    Verify,
+    Export(PathBuf),
+    Import {
+        path: PathBuf,
+        fresh: bool,
+        force: bool
+    },
+    Reset,
+    Help
+}
+
+// Размер проверяется сразу, чтобы ни в окне, ни в файле не появилось поле, которое игра не откроет
+fn parse_size(value: &str) -> Result<(i32, i32), String> {
+    let (width, height) = value.split_once(['x', 'х'])
+        .ok_or_else(|| format!("{}: {}", lang::strings().cli_size_format, value))?;
+    let range = settings::MIN_BOARD_SIZE..=settings::MAX_BOARD_SIZE;
+
+    match (width.parse::<i32>(), height.parse::<i32>()) {
+        (Ok(width), Ok(height)) if range.contains(&width) && range.contains(&height) => Ok((width, height)),
+        (Ok(_), Ok(_)) => Err(format!(
+            "{} {}-{}: {}",
+            lang::strings().cli_size_range,
+            settings::MIN_BOARD_SIZE,
+            settings::MAX_BOARD_SIZE,
+            value
+        )),
+        _ => Err(format!("{}: {}", lang::strings().cli_invalid_size, value))
+    }
+}
+
+fn value<'a>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> Result<&'a String, String> {
+    args.next().ok_or_else(|| format!("{} {}", lang::strings().cli_missing_value, option))
+}
+
+fn argument(args: &[String], name: &str) -> Result<String, String> {
+    match args {
+        [value] => Ok(value.clone()),
+        _ => Err(format!("{}: {}", lang::strings().cli_one_argument, name))
+    }
+}
+
+// Общие параметры, которые можно указать перед любой командой
+pub fn parse_location(args: &[String]) -> Result<(Option<storage::Location>, Vec<String>), String> {
+    let mut location = None;
+    let mut rest = vec![];
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => location = Some(storage::Location::Custom(PathBuf::from(value(&mut args, arg)?))),
+            "--portable" => location = Some(storage::Location::Portable),
+            _ => {
+                rest.push(arg.clone());
+                rest.extend(args.cloned());
+                break;
+            }
+        }
+    }
+
+    Ok((location, rest))
+}
+
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Ok(Command::Play)
+    };
+
+    match command {
+        "play" if rest.is_empty() => Ok(Command::Play),
+        "new" => {
+            let (mut seed, mut size, mut out, mut force) = (None, None, None, false);
+            let mut args = rest.iter();
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--seed" => {
+                        let value = value(&mut args, arg)?;
+                        seed = Some(value.parse().map_err(|_| format!("{}: {}", lang::strings().cli_invalid_seed, value))?);
+                    },
+                    "--size" => size = Some(parse_size(value(&mut args, arg)?)?),
+                    "--out" => out = Some(PathBuf::from(value(&mut args, arg)?)),
+                    "--force" => force = true,
+                    _ => return Err(format!("{}: {}", lang::strings().cli_unknown_option, arg))
+                }
+            }
+
+            Ok(Command::New { seed, size, out, force })
+        },
+        "solve" => Ok(Command::Solve(PathBuf::from(argument(rest, lang::strings().cli_game_file)?))),
+        "check" => Ok(Command::Check(argument(rest, lang::strings().cli_word)?)),
+        "leaders" if rest.is_empty() => Ok(Command::Leaders),
+        "verify" if rest.is_empty() => Ok(Command::Verify),
+        "export" => Ok(Command::Export(PathBuf::from(argument(rest, lang::strings().cli_file)?))),
+        "import" => {
+            let (mut path, mut fresh, mut force) = (None, false, false);
+            let usage = lang::strings().cli_import_usage;
+
+            for arg in rest {
+                match arg.as_str() {
+                    "--fresh" => fresh = true,
+                    "--force" => force = true,
+                    _ if arg.starts_with("--") || path.is_some() => {
+                        return Err(String::from(usage));
+                    },
+                    _ => path = Some(PathBuf::from(arg))
+                }
+            }
+
+            match path {
+                Some(path) => Ok(Command::Import { path, fresh, force }),
+                None => Err(String::from(usage))
+            }
+        },
+        "reset" if rest.is_empty() => Ok(Command::Reset),
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        _ => Err(format!("{}: {}", lang::strings().cli_unknown_command, args.join(" ")))
+    }
+}
+
+fn print_board(field: &Field) {
+    for y in 0..field.get_height() {
+        let row: String = (0..field.get_width())
+            .map(|x| {
+                let ceil = field.get(x, y);
+                match ceil.ceil_type {
+                    CeilType::Active => ceil.letter,
+                    CeilType::Bonus => '!',
+                    CeilType::Empty => '.'
+                }
+            })
+            .collect();
+
+        println!("{}", row);
+    }
+}
+
+fn load() -> super::Config {
+    let loaded = load_config();
+
+    if let Some(warning) = loaded.warning {
+        eprintln!("{}", warning);
+    }
+
+    // Дальше сообщения выводятся на языке из настроек
+    loaded.value.preferences.apply();
+
+    loaded.value
+}
+
+// Начатую и не законченную игру без --force не заменяем, иначе она пропадёт молча
+fn is_started(config: &super::Config) -> bool {
+    let active = &config.profiles[config.active_profile].field;
+
+    !active.is_finished() && !active.get_moves().is_empty()
+}
+
+// Изменения сохранения из командной строки записываются сразу, без отложенного сохранения
+fn store_config(config: &super::Config) -> Result<(), String> {
+    storage::store(&config.to_save()).map_err(|err| format!("{}: {}", lang::strings().cannot_save, err))
+}
+
+fn execute(command: Command) -> Result<i32, String> {
+    match command {
+        Command::Play => {
+            app().map_err(|err| err.to_string())?;
+        },
+        Command::New { seed, size, out, force } => {
+            let config = load();
+            let (width, height) = size.unwrap_or(config.preferences.board_size());
+            let field = Field::new(width, height);
+
+            match seed {
+                Some(seed) => field.generate_with_seed(seed),
+                None => field.generate()
+            }
+
+            match out {
+                Some(path) => {
+                    save::export_game(&field, &path).map_err(|err| format!("{}: {}", lang::strings().cannot_export, err))?;
+                    print_board(&field);
+                },
+                None if !force && is_started(&config) => {
+                    return Err(String::from(lang::strings().cli_new_unfinished));
+                },
+                None => {
+                    let mut config = config;
+                    config.profiles[config.active_profile].field = field;
+                    store_config(&config)?;
+                    app().map_err(|err| err.to_string())?;
+                }
+            }
+        },
+        Command::Solve(path) => {
+            let field = save::import_game(&path).map_err(|err| format!("{}: {}", lang::strings().cannot_import, err))?;
+
+            for word in solver::solve(&field) {
+                println!("{}", word.to_lowercase());
+            }
+        },
+        Command::Check(word) => {
+            let is_word = dictionary::get().is_word(&word.to_lowercase());
+            println!("{}", if is_word { lang::strings().cli_in_dictionary } else { lang::strings().cli_not_in_dictionary });
+
+            return Ok(if is_word { 0 } else { 1 });
+        },
+        Command::Leaders => {
+            let config = load();
+
+            for (idx, record) in config.table_of_leaders.iter().enumerate() {
+                println!(
+                    "{:>2}. {:>6}  {:<12} {:<16} {}  {:>6}  {} {}",
+                    idx + 1,
+                    record.score,
+                    if record.player.is_empty() { "-" } else { &record.player },
+                    record.longest_word.to_uppercase(),
+                    leaders::format_date(record.date),
+                    leaders::format_duration(record.duration),
+                    record.words,
+                    lang::strings().words_short
+                );
+            }
+        },
+        Command::Verify => {
+            let config = load();
+            let (lines, tampered) = verify::report(&config.table_of_leaders);
+
+            for line in lines {
+                println!("{}", line);
+            }
+
+            return Ok(if tampered > 0 { 1 } else { 0 });
+        },
+        Command::Export(path) => {
+            let config = load();
+
+            save::export_game(&config.get_profile().field, &path)
+                .map_err(|err| format!("{}: {}", lang::strings().cannot_export, err))?;
+        },
+        Command::Import { path, fresh, force } => {
+            let mut config = load();
+
+            if !force && is_started(&config) {
+                return Err(String::from(lang::strings().cli_import_unfinished));
+            }
+
+            let field = save::import_game(&path).map_err(|err| format!("{}: {}", lang::strings().cannot_import, err))?;
+
+            if fresh {
+                field.restart();
+            }
+
+            config.profiles[config.active_profile].field = field;
+            store_config(&config)?;
+        },
+        Command::Reset => {
+            storage::reset().map_err(|err| err.to_string())?;
+        },
+        Command::Help => println!("{}", lang::strings().cli_usage)
+    }
+
+    Ok(0)
+}
+
+// Код возврата: 0 - успех, 1 - отрицательный результат проверки, 2 - ошибка
+pub fn run(args: &[String]) -> i32 {
+    let command = parse_location(args).and_then(|(location, args)| {
+        if let Some(location) = location {
+            storage::set_location(location);
+        }
+
+        parse(&args)
+    });
+
+    let command = match command {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, lang::strings().cli_usage);
+            return 2;
+        }
+    };
+
+    match execute(command) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_simple_commands() {
+        assert!(matches!(parse(&[]), Ok(Command::Play)));
+        assert!(matches!(parse(&args("play")), Ok(Command::Play)));
+        assert!(matches!(parse(&args("leaders")), Ok(Command::Leaders)));
+        assert!(matches!(parse(&args("verify")), Ok(Command::Verify)));
+        assert!(matches!(parse(&args("reset")), Ok(Command::Reset)));
+        assert!(matches!(parse(&args("help")), Ok(Command::Help)));
+        assert!(matches!(parse(&args("--help")), Ok(Command::Help)));
+        assert!(matches!(parse(&args("check кот")), Ok(Command::Check(word)) if word == "кот"));
+        assert!(matches!(parse(&args("solve game.ron")), Ok(Command::Solve(path)) if path == Path::new("game.ron")));
+        assert!(matches!(parse(&args("export game.ron")), Ok(Command::Export(path)) if path == Path::new("game.ron")));
+    }
+
+    #[test]
+    fn parses_new() {
+        assert!(matches!(
+            parse(&args("new")),
+            Ok(Command::New { seed: None, size: None, out: None, force: false })
+        ));
+        assert!(matches!(
+            parse(&args("new --seed 42 --size 10x12 --out game.ron --force")),
+            Ok(Command::New { seed: Some(42), size: Some((10, 12)), out: Some(path), force: true })
+                if path == Path::new("game.ron")
+        ));
+        assert!(parse(&args("new --seed")).is_err());
+        assert!(parse(&args("new --seed abc")).is_err());
+    }
+
+    #[test]
+    fn parses_import() {
+        assert!(matches!(
+            parse(&args("import game.ron")),
+            Ok(Command::Import { fresh: false, force: false, .. })
+        ));
+        assert!(matches!(parse(&args("import game.ron --fresh")), Ok(Command::Import { fresh: true, .. })));
+        assert!(matches!(
+            parse(&args("import --fresh game.ron")),
+            Ok(Command::Import { path, fresh: true, force: false }) if path == Path::new("game.ron")
+        ));
+        assert!(matches!(
+            parse(&args("import --force game.ron --fresh")),
+            Ok(Command::Import { path, fresh: true, force: true }) if path == Path::new("game.ron")
+        ));
+        assert!(parse(&args("import")).is_err());
+        assert!(parse(&args("import --force")).is_err());
+        assert!(parse(&args("import a.ron b.ron")).is_err());
+        assert!(parse(&args("import game.ron --unknown")).is_err());
+    }
+
+    #[test]
+    fn parses_size() {
+        assert_eq!(parse_size("8x10"), Ok((8, 10)));
+        assert_eq!(parse_size("8х10"), Ok((8, 10)));
+        assert_eq!(parse_size("20x8"), Ok((20, 8)));
+        assert!(parse_size("7x10").is_err());
+        assert!(parse_size("8x21").is_err());
+        assert!(parse_size("30000x30000").is_err());
+        assert!(parse_size("0x5").is_err());
+        assert!(parse_size("8x").is_err());
+        assert!(parse_size("x8").is_err());
+        assert!(parse_size("-1x5").is_err());
+        assert!(parse_size("99999999999x5").is_err());
+        assert!(parse_size("8").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert!(parse(&args("new --unknown")).is_err());
+        assert!(parse(&args("play --fast")).is_err());
+        assert!(parse(&args("leaders 10")).is_err());
+        assert!(parse(&args("check кот пёс")).is_err());
+        assert!(parse(&args("fly")).is_err());
+    }
+
+    #[test]
+    fn parses_location() {
+        let (location, rest) = parse_location(&args("--portable leaders")).unwrap();
+
+        assert!(matches!(location, Some(storage::Location::Portable)));
+        assert_eq!(rest, args("leaders"));
+        assert!(parse_location(&args("--config")).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+pub struct Dictionary {
+    words: HashSet<String>,
+    prefixes: HashSet<String>
+}
+
+static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+
+pub fn get() -> &'static Dictionary {
+    DICTIONARY.get_or_init(|| Dictionary::new(include_str!("../../assets/words.txt")))
+}
+
+// На поле буквы Е и Ё, И и Й взаимозаменяемы, поэтому префиксы хранятся без них
+fn normalize(word: &str) -> String {
+    word.replace('ё', "е").replace('й', "и")
+}
+
+impl Dictionary {
+    pub fn new(content: &str) -> Self {
+        let mut words = HashSet::new();
+        let mut prefixes = HashSet::new();
+
+        for line in content.split('\n') {
+            let word = line.trim().to_lowercase();
+
+            if word.is_empty() {
+                continue;
+            }
+
+            let normalized = normalize(&word);
+            for (idx, _) in normalized.char_indices().skip(1) {
+                prefixes.insert(normalized[..idx].to_string());
+            }
+            prefixes.insert(normalized);
+
+            words.insert(word);
+        }
+
+        Dictionary {
+            words,
+            prefixes
+        }
+    }
+
+    pub fn is_word(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+
+        self.words.contains(&word) ||
+            self.words.contains(&word.replace('е', "ё")) ||
+            self.words.contains(&word.replace('и', "й"))
+    }
+
+    pub fn is_prefix(&self, prefix: &str) -> bool {
+        self.prefixes.contains(&normalize(&prefix.to_lowercase()))
+    }
+}
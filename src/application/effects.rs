@@ -0,0 +1,118 @@
+use std::f32::consts::TAU;
+use fltk::{draw::*, enums};
+use rand::Rng;
+use crate::application::{preferences, theme};
+use crate::application::animation::{Easing, Tween};
+use crate::application::field::Clearing;
+use crate::application::settings::*;
+use crate::application::ui::{mix, rgb};
+
+// Очки, всплывающие над клеткой принятого слова
+struct Popup {
+    x: i32,
+    y: i32,
+    text: String,
+    tween: Tween
+}
+
+// Искра от убранной бонусной линии: разлетается из середины клетки и падает
+struct Particle {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    tween: Tween
+}
+
+// Эффекты поверх поля. Их запускают события поля, а состояние игры они не меняют
+#[derive(Default)]
+pub struct Effects {
+    popups: Vec<Popup>,
+    particles: Vec<Particle>,
+    flash: Option<Tween>
+}
+
+impl Effects {
+    pub fn push(&mut self, clearing: &Clearing) {
+        if preferences::current().reduced_motion {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+
+        for cleared in &clearing.removed {
+            let x = cleared.x * CELL_SIZE + CELL_SIZE / 2;
+            let y = cleared.y * CELL_SIZE + CELL_SIZE / 2 + OFFSET_Y;
+
+            // Клетки самого слова были выделены, остальные попали под бонусную линию
+            if cleared.ceil.checked > 0 {
+                self.popups.push(Popup {
+                    x,
+                    y,
+                    text: format!("+{}", cleared.scores),
+                    tween: Tween::start(POPUP_MS, Easing::EaseOut)
+                });
+            }
+
+            if cleared.is_bonus_line {
+                for _ in 0..PARTICLES_PER_CEIL {
+                    let angle: f32 = rng.gen_range(0.0, TAU);
+                    let distance: f32 = rng.gen_range(0.3, 1.0) * PARTICLE_DISTANCE;
+
+                    self.particles.push(Particle {
+                        x: x as f32,
+                        y: y as f32,
+                        dx: angle.cos() * distance,
+                        dy: angle.sin() * distance,
+                        tween: Tween::start(PARTICLE_MS, Easing::EaseOut)
+                    });
+                }
+            }
+        }
+
+        self.flash = Some(Tween::start(FLASH_MS, Easing::Linear));
+    }
+
+    // Насколько сейчас подсвечен счёт в заголовке, от 1 сразу после слова до 0
+    pub fn flash(&self) -> f32 {
+        self.flash.map_or(0.0, |tween| 1.0 - tween.value())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Effects::default();
+    }
+
+    // Рисует эффекты в пределах поля и забывает закончившиеся
+    pub fn draw(&mut self, width: i32, height: i32) {
+        self.popups.retain(|popup| !popup.tween.is_finished());
+        self.particles.retain(|particle| !particle.tween.is_finished());
+
+        let theme = theme::current();
+        let (font, font_size) = (font(), size());
+
+        push_clip(0, OFFSET_Y, width, height);
+
+        for particle in &self.particles {
+            let t = particle.tween.value();
+            let x = particle.x + particle.dx * t;
+            let y = particle.y + particle.dy * t + PARTICLE_FALL * t * t;
+            let size = ((1.0 - t) * PARTICLE_SIZE as f32).ceil() as i32;
+
+            draw_rect_fill(x as i32 - size / 2, y as i32 - size / 2, size, size, rgb(theme.bonus_mark.to));
+        }
+
+        set_font(enums::Font::CourierBold, 14);
+
+        for popup in &self.popups {
+            let t = popup.tween.value();
+            let (text_width, _) = measure(&popup.text, false);
+
+            // Без прозрачности очки гаснут, приближаясь к цвету клетки
+            set_draw_color(mix(theme.outline, theme.tile, t * t));
+            draw_text(&popup.text, popup.x - text_width / 2, popup.y - (POPUP_RISE * t) as i32);
+        }
+
+        pop_clip();
+        set_font(font, font_size);
+    }
+}
@@ -1,8 +1,9 @@
-use rand::Rng;
+use rand::{Isaac64Rng, Rng, SeedableRng};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Serialize,Deserialize};
-use crate::application::{settings, sound};
+use crate::application::{dictionary, settings};
 
 pub enum Direction {
     LeftToRight,
@@ -16,6 +17,58 @@ pub enum Direction {
     None
 }
 
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+pub enum CheckOutcome {
+    Ignored,
+    Selected,
+    Accepted(WordRecord),
+    Wrong
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WordRecord {
+    pub word: String,
+    pub path: Vec<(i32, i32)>,
+    pub base_score: i32,
+    pub bonus_score: i32,
+    #[serde(default)]
+    pub rarity_score: i32,
+    pub timestamp: u64
+}
+
+// Действие игрока над полем, из них складывается запись игры
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Move {
+    Select(i32, i32),
+    Deselect
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayStep {
+    // Миллисекунды от начала игры
+    pub time: u64,
+    pub action: Move
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum CeilType {
     Active,
@@ -23,57 +76,135 @@ pub enum CeilType {
     Bonus
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug)]
 pub struct Ceil {
     pub letter: char,
     pub checked: i32,
     pub ceil_type: CeilType
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Клетка, которая упала на освободившееся место в своём столбце
+#[derive(Copy, Clone, Debug)]
+pub struct Fall {
+    pub x: i32,
+    pub from_y: i32,
+    pub to_y: i32
+}
+
+// Клетка, убранная вместе с принятым словом, с тем, что в ней было
+#[derive(Copy, Clone, Debug)]
+pub struct ClearedCeil {
+    pub x: i32,
+    pub y: i32,
+    pub ceil: Ceil,
+    // Сколько очков принесла именно эта клетка
+    pub scores: i32,
+    pub is_bonus_line: bool
+}
+
+// Предпросмотр выделения: слово ли это и что за него дадут. Поле при этом не меняется
+#[derive(Clone, Debug, Default)]
+pub struct WordProjection {
+    pub word: String,
+    pub is_word: bool,
+    // Выделение касается бонусной клетки, и вместе со словом уйдут целые строки
+    pub is_bonus: bool,
+    pub base_score: i32,
+    pub bonus_score: i32,
+    // Сколько букв уйдёт с поля вместе со словом, включая бонусные линии, но без пустых клеток на них
+    pub cleared: usize
+}
+
+// Что изменилось на поле после принятого слова. Поле уже в новом состоянии,
+// а это нужно только чтобы показать переход
+#[derive(Clone, Debug, Default)]
+pub struct Clearing {
+    pub removed: Vec<ClearedCeil>,
+    pub falls: Vec<Fall>
+}
+
+#[derive(Debug, Clone)]
 pub struct Field {
     pub scores: RefCell<i32>,
     is_word_ready: RefCell<bool>,
     most_lengthy_word: RefCell<String>,
-    height: i32,
-    width: i32,
+    word_log: RefCell<Vec<WordRecord>>,
+    seed: RefCell<u64>,
+    started_at: RefCell<u64>,
+    mode: RefCell<GameMode>,
+    finished: RefCell<bool>,
+    moves: RefCell<Vec<ReplayStep>>,
+    // Последнее принятое слово, которое ещё не забрал экран
+    last_clearing: RefCell<Option<Clearing>>,
+    // Поле в момент начала игры, чтобы её можно было сыграть заново
+    initial: RefCell<Vec<Vec<Ceil>>>,
+    height: RefCell<i32>,
+    width: RefCell<i32>,
     data: RefCell<Vec<Vec<Ceil>>>
 }
 
 impl  Field {
     pub fn new(width: i32, height: i32) -> Self {
-        let data: RefCell<Vec<Vec<Ceil>>> = RefCell::new(Vec::new());
+        let field = Field {
+            scores: RefCell::new(0),
+            is_word_ready: RefCell::new(false),
+            width: RefCell::new(width),
+            height: RefCell::new(height),
+            data: RefCell::new(Vec::new()),
+            most_lengthy_word: RefCell::new(String::new()),
+            word_log: RefCell::new(Vec::new()),
+            seed: RefCell::new(0),
+            started_at: RefCell::new(now()),
+            mode: RefCell::new(GameMode::default()),
+            finished: RefCell::new(false),
+            moves: RefCell::new(Vec::new()),
+            last_clearing: RefCell::new(None),
+            initial: RefCell::new(Vec::new())
+        };
 
-        for _y in 0..height {
-            let mut nvec: Vec<Ceil> = Vec::new();
+        field.resize(width, height);
 
-            for _x in 0..width {
-                nvec.push(Ceil {
-                    letter: ' ',
-                    checked: 0,
-                    ceil_type: CeilType::Active
-                })
-            }
+        field
+    }
 
-            data.borrow_mut().push(nvec);
-        }
+    // Новый размер поля, клетки остаются без букв до следующей генерации
+    pub fn resize(&self, width: i32, height: i32) {
+        let ceil = Ceil {
+            letter: ' ',
+            checked: 0,
+            ceil_type: CeilType::Active
+        };
 
-        Field {
-            scores: RefCell::new(0),
-            is_word_ready: RefCell::new(false),
-            width,
-            height,
-            data,
-            most_lengthy_word: RefCell::new(String::new())
-        }
+        *self.width.borrow_mut() = width;
+        *self.height.borrow_mut() = height;
+        *self.data.borrow_mut() = vec![vec![ceil; width as usize]; height as usize];
+        self.initial.borrow_mut().clear();
+    }
+
+    // Замена состояния поля на другое, например при смене игрока
+    pub fn assign(&self, other: &Field) {
+        *self.scores.borrow_mut() = other.get_scores();
+        *self.is_word_ready.borrow_mut() = other.is_word();
+        *self.most_lengthy_word.borrow_mut() = other.get_longest_word();
+        *self.word_log.borrow_mut() = other.get_word_log();
+        *self.seed.borrow_mut() = other.get_seed();
+        *self.started_at.borrow_mut() = other.get_started_at();
+        *self.mode.borrow_mut() = other.get_mode();
+        *self.finished.borrow_mut() = other.is_finished();
+        *self.moves.borrow_mut() = other.get_moves();
+        *self.width.borrow_mut() = other.get_width();
+        *self.height.borrow_mut() = other.get_height();
+        *self.data.borrow_mut() = other.get_board();
+        *self.initial.borrow_mut() = other.get_initial_board();
+        self.last_clearing.take();
     }
 
     pub fn get_width(&self) -> i32 {
-        self.width
+        *self.width.borrow()
     }
 
     pub fn get_height(&self) -> i32 {
-        self.height
+        *self.height.borrow()
     }
 
     pub fn get_scores(&self) -> i32 {
@@ -93,7 +224,133 @@ impl  Field {
         *scorestochange = scores;
     }
 
+    pub fn get_seed(&self) -> u64 {
+        *self.seed.borrow()
+    }
+
+    pub fn set_seed(&self, seed: u64) {
+        *self.seed.borrow_mut() = seed;
+    }
+
+    pub fn get_mode(&self) -> GameMode {
+        *self.mode.borrow()
+    }
+
+    pub fn set_mode(&self, mode: GameMode) {
+        *self.mode.borrow_mut() = mode;
+    }
+
+    pub fn get_started_at(&self) -> u64 {
+        *self.started_at.borrow()
+    }
+
+    pub fn set_started_at(&self, started_at: u64) {
+        *self.started_at.borrow_mut() = started_at;
+    }
+
+    // Длительность игры в секундах, у сохранений без времени начала она неизвестна
+    pub fn get_duration(&self) -> u64 {
+        match *self.started_at.borrow() {
+            0 => 0,
+            started_at => now().saturating_sub(started_at)
+        }
+    }
+
+    // Законченную игру можно только просматривать
+    pub fn is_finished(&self) -> bool {
+        *self.finished.borrow()
+    }
+
+    pub fn set_finished(&self, finished: bool) {
+        *self.finished.borrow_mut() = finished;
+    }
+
+    pub fn get_word_log(&self) -> Vec<WordRecord> {
+        self.word_log.borrow().clone()
+    }
+
+    pub fn set_word_log(&self, word_log: Vec<WordRecord>) {
+        *self.word_log.borrow_mut() = word_log;
+    }
+
+    pub fn clear_word_log(&self) {
+        self.word_log.borrow_mut().clear();
+    }
+
+    // Дополнительные очки за последнее принятое слово, например за его редкость
+    pub fn reward_last_word(&self, scores: i32) {
+        if let Some(record) = self.word_log.borrow_mut().last_mut() {
+            record.rarity_score += scores;
+        }
+
+        self.set_scores(self.get_scores() + scores);
+    }
+
+    pub fn get_moves(&self) -> Vec<ReplayStep> {
+        self.moves.borrow().clone()
+    }
+
+    pub fn set_moves(&self, moves: Vec<ReplayStep>) {
+        *self.moves.borrow_mut() = moves;
+    }
+
+    fn record_move(&self, action: Move) {
+        let time = now_ms().saturating_sub(self.get_started_at() * 1000);
+
+        self.moves.borrow_mut().push(ReplayStep { time, action });
+    }
+
+    pub fn get_board(&self) -> Vec<Vec<Ceil>> {
+        self.data.borrow().clone()
+    }
+
+    pub fn set_board(&self, board: Vec<Vec<Ceil>>) {
+        *self.data.borrow_mut() = board;
+    }
+
+    pub fn get_initial_board(&self) -> Vec<Vec<Ceil>> {
+        self.initial.borrow().clone()
+    }
+
+    pub fn set_initial_board(&self, board: Vec<Vec<Ceil>>) {
+        *self.initial.borrow_mut() = board;
+    }
+
+    pub fn new_game(&self) {
+        self.generate();
+        self.reset_progress();
+    }
+
+    // Та же игра с самого начала. Если начальное поле неизвестно, игра начинается с текущего
+    pub fn restart(&self) {
+        if !self.initial.borrow().is_empty() {
+            self.set_board(self.get_initial_board());
+        }
+
+        self.set_started_at(now());
+        self.reset_progress();
+    }
+
+    fn reset_progress(&self) {
+        self.set_longest_word(String::new());
+        self.set_scores(0);
+        self.clear_word_log();
+        self.moves.borrow_mut().clear();
+        self.set_finished(false);
+        *self.is_word_ready.borrow_mut() = false;
+        self.last_clearing.take();
+    }
+
     pub fn generate(&self) {
+        self.generate_with_seed(rand::thread_rng().gen());
+    }
+
+    pub fn generate_with_seed(&self, seed: u64) {
+        let mut rng: Isaac64Rng = SeedableRng::from_seed(&[seed][..]);
+
+        *self.seed.borrow_mut() = seed;
+        *self.started_at.borrow_mut() = now();
+
         let mut letter_frequency = HashMap::new();
         letter_frequency.insert('А', 8.01);
         letter_frequency.insert('Б', 1.59);
@@ -131,16 +388,20 @@ impl  Field {
 
         let mut string = String::new();
 
+        // Порядок букв не должен зависеть от HashMap, иначе одно и то же зерно даст разные поля
+        let mut letter_frequency: Vec<(char, f64)> = letter_frequency.into_iter().collect();
+        letter_frequency.sort_by_key(|(letter, _)| *letter);
+
         for (letter, percent) in &letter_frequency {
             let count = (percent * 100.0) as usize;
 
             string.push_str(String::from(*letter).repeat(count).as_str());
         }
 
-        for j in 0..self.height {
-            let random_type = rand::thread_rng().gen_range(-self.width, self.width);
-            for i in 0..self.width {
-                let mut random_index = rand::thread_rng().gen_range(0, string.chars().enumerate().count());
+        for j in 0..self.get_height() {
+            let random_type = rng.gen_range(-self.get_width(), self.get_width());
+            for i in 0..self.get_width() {
+                let mut random_index = rng.gen_range(0, string.chars().enumerate().count());
 
                 for ch in string.chars() {
                     if random_index == 0 {
@@ -162,59 +423,32 @@ impl  Field {
                 }
             }
         }
-    }
 
-    pub fn is_blocked(&self) -> bool {
-        for i in 0..self.height {
-            for j in 0..self.width {
-                if self.get(j,i).checked < 0 {
-                    return true;
-                }
-            }
-        }
+        self.set_initial_board(self.get_board());
+    }
 
-        false
+    pub fn take_clearing(&self) -> Option<Clearing> {
+        self.last_clearing.take()
     }
 
     pub fn is_valid(&self, x: i32, y: i32) -> bool {
-        return x > 0 && y > 0 && x < self.width && y < self.height;
+        return x > 0 && y > 0 && x < self.get_width() && y < self.get_height();
     }
 
     pub fn get(&self, x: i32, y: i32) -> Ceil {
         self.data.borrow()[y as usize][x as usize]
     }
 
-    pub fn set(&self, x: i32, y: i32, ceil: Ceil) {
-        self.data.borrow_mut()[y as usize][x as usize] = ceil;
-    }
-
     pub fn is_word(&self) -> bool {
         *self.is_word_ready.borrow()
     }
 
     pub fn check_word(&self, word: String) -> bool {
-
-        let dictionary = include_str!("../../assets/words.txt").split("\n");
-
-        let word2 = word.replace("е","ё");
-        let word3 = word.replace("и", "й");
-
-        for word_from_dictionary in dictionary {
-
-            let normalized_word = word_from_dictionary.trim().to_string().to_lowercase();
-
-            if normalized_word.eq(&word) ||
-                normalized_word.eq(&word2) ||
-                normalized_word.eq(&word3) {
-                return true;
-            }
-        }
-
-        false
+        dictionary::get().is_word(&word)
     }
 
     pub fn is_checked(&self, x: i32, y: i32) -> i32 {
-        if x > self.width - 1 || y > self.height - 1 || x < 0 || y < 0 {
+        if x > self.get_width() - 1 || y > self.get_height() - 1 || x < 0 || y < 0 {
             return -1;
         }
 
@@ -226,8 +460,8 @@ impl  Field {
         let mut max_x: i32 = -1;
         let mut max_y: i32 = -1;
 
-        for i in 0..self.height {
-            for j in 0..self.width {
+        for i in 0..self.get_height() {
+            for j in 0..self.get_width() {
                 if max < self.data.borrow()[i as usize][j as usize].checked {
                     max = self.data.borrow()[i as usize][j as usize].checked;
                     max_x = j;
@@ -239,24 +473,118 @@ impl  Field {
         (max,max_x,max_y)
     }
 
-    pub fn down(&self, x: i32, mut y: i32) {
-        let mut data = self.data.borrow_mut();
+    // Строки, которые уйдут вместе со словом, потому что выделение касается их бонусной клетки
+    fn get_bonus_lines(&self) -> Vec<i32> {
+        (0..self.get_height())
+            .filter(|y| self.is_on_the_bonus_line(0, *y))
+            .collect()
+    }
 
-        let mut y_n = y - 1;
-        while y_n > -1 && data[y_n as usize][x as usize].checked >= 0 {
-            let temp = data[y as usize][x as usize];
-            data[y as usize][x as usize] = data[y_n as usize][x as usize];
-            data[y_n as usize][x as usize] = temp;
-            y_n -= 1;
-            y -= 1;
+    // Клетки, которые уйдут вместе с выделенным словом, и очки за них: базовые и за бонус.
+    // Общая часть приёма слова и его предпросмотра, поле не меняет
+    fn score_cells(data: &[Vec<Ceil>], bonus_lines: &[i32]) -> (Vec<ClearedCeil>, i32, i32) {
+        let mut scores_append = 0;
+        let mut scores_base = 0;
+        let mut removed = vec![];
+
+        for (y, row) in data.iter().enumerate() {
+            let y = y as i32;
+
+            for (x, ceil) in row.iter().enumerate() {
+                let is_checked = ceil.checked > 0;
+                let is_bonus_line = bonus_lines.contains(&y);
+                if is_checked {
+                    scores_base += settings::SCORES_FOR_CHECKED;
+                }
+                if is_checked || is_bonus_line {
+                    let ceil_scores = if is_checked && !bonus_lines.is_empty() {
+                        settings::SCORES_FOR_BONUS_AND_CHECKED
+                    } else if !is_checked && is_bonus_line {
+                        settings::SCORES_FOR_BONUS_LINE
+                    } else {
+                        settings::SCORES_FOR_CHECKED
+                    };
+                    scores_append += ceil_scores;
+                    removed.push(ClearedCeil {
+                        x: x as i32,
+                        y,
+                        ceil: *ceil,
+                        scores: ceil_scores * 2,
+                        is_bonus_line
+                    });
+                }
+            }
+        }
+
+        (removed, scores_base * 2, (scores_append - scores_base) * 2)
+    }
+
+    // Что даст выделенное слово, если принять его сейчас
+    pub fn project(&self) -> WordProjection {
+        let word = self.get_word();
+        let is_word = !word.is_empty() && self.check_word(word.to_lowercase());
+        let bonus_lines = self.get_bonus_lines();
+        let (cleared, base_score, bonus_score) = Self::score_cells(&self.data.borrow(), &bonus_lines);
+
+        WordProjection {
+            word,
+            is_word,
+            is_bonus: !bonus_lines.is_empty(),
+            base_score,
+            bonus_score,
+            cleared: cleared.iter().filter(|cleared| !matches!(cleared.ceil.ceil_type, CeilType::Empty)).count()
         }
     }
 
+    // Убранные клетки уходят наверх своего столбца пустыми, остальные опускаются, не меняя порядка
+    fn collapse(data: &mut [Vec<Ceil>], removed: &[ClearedCeil]) -> Vec<Fall> {
+        let empty = Ceil {
+            letter: ' ',
+            checked: 0,
+            ceil_type: CeilType::Empty
+        };
+        let mut falls = vec![];
+
+        for x in 0..data.first().map_or(0, |row| row.len()) {
+            let kept: Vec<(i32, Ceil)> = (0..data.len())
+                .filter(|y| !removed.iter().any(|cleared| cleared.x == x as i32 && cleared.y == *y as i32))
+                .map(|y| (y as i32, data[y][x]))
+                .collect();
+            let gap = data.len() - kept.len();
+
+            for row in data.iter_mut().take(gap) {
+                row[x] = empty;
+            }
+
+            for (idx, (from_y, ceil)) in kept.into_iter().enumerate() {
+                let to_y = (gap + idx) as i32;
+                data[to_y as usize][x] = ceil;
+
+                if to_y != from_y && !matches!(ceil.ceil_type, CeilType::Empty) {
+                    falls.push(Fall { x: x as i32, from_y, to_y });
+                }
+            }
+        }
+
+        falls
+    }
+
+    // Можно ли продолжить начатое выделение этой клеткой
+    pub fn can_extend(&self, x: i32, y: i32) -> bool {
+        let (max_val, max_x, max_y) = self.find_max();
+
+        max_val > 0 && self.is_checked(x, y) == 0 &&
+            (max_x - x).abs() <= 1 && (max_y - y).abs() <= 1 &&
+            matches!(self.get(x, y).ceil_type, CeilType::Active)
+    }
+
     pub fn deselect(&self) {
+        self.record_move(Move::Deselect);
+
         let mut data = self.data.borrow_mut();
 
-        for i in 0..self.height {
-            for j in 0..self.width {
+        for i in 0..self.get_height() {
+            for j in 0..self.get_width() {
                 data[i as usize][j as usize] = Ceil {
                     checked: 0,
                     letter: data[i as usize][j as usize].letter,
@@ -264,29 +592,23 @@ impl  Field {
                 };
             }
         }
-
-        sound::play_undo();
     }
 
-    pub fn try_check(&self, x: i32, y: i32) {
+    pub fn try_check(&self, x: i32, y: i32) -> CheckOutcome {
+        self.record_move(Move::Select(x, y));
 
         if matches!(self.get(x,y).ceil_type, CeilType::Empty) ||
             matches!(self.get(x,y).ceil_type, CeilType::Bonus){
-            return
+            return CheckOutcome::Ignored
         };
 
-        let mut bonus_lines = vec![];
-
-        for y in 0..self.height {
-            if self.is_on_the_bonus_line(0,y) {
-                bonus_lines.push(y);
-            }
-        }
+        let bonus_lines = self.get_bonus_lines();
 
         let (max_val,max_x,max_y) = self.find_max();
 
         let word = self.get_word();
         let word2 = word.clone();
+        let path = self.get_path();
 
         let mut data = self.data.borrow_mut();
 
@@ -297,15 +619,17 @@ impl  Field {
             is_checked;
         let is_same = (x == max_x) && (y == max_y);
 
+        let outcome;
+
         if is_near {
-            sound::play_click();
+            outcome = CheckOutcome::Selected;
             data[y as usize][x as usize] = Ceil {
                 checked: max_val + 1,
                 letter:data[y as usize][x as usize].letter,
                 ceil_type: CeilType::Active
             };
         } else if !is_exists {
-            sound::play_click();
+            outcome = CheckOutcome::Selected;
             data[y as usize][x as usize] = Ceil {
                 checked: 1,
                 letter: data[y as usize][x as usize].letter,
@@ -313,48 +637,39 @@ impl  Field {
             };
         } else if is_same && self.check_word(word.to_lowercase()) {
             let scores = self.scores.take();
-            let mut scores_append = 0;
-
-            for y in 0..self.height {
-                for x in 0..self.width {
-                    let is_checked = data[y as usize][x as usize].checked > 0;
-                    let is_bonus_line = bonus_lines.contains(&y);
-                    if is_checked || is_bonus_line {
-                        scores_append += if is_checked && bonus_lines.len() > 0 {
-                            settings::SCORES_FOR_BONUS_AND_CHECKED
-                        } else if !is_checked && is_bonus_line {
-                            settings::SCORES_FOR_BONUS_LINE
-                        } else {
-                            settings::SCORES_FOR_CHECKED
-                        };
-                        data[y as usize][x as usize] = Ceil {
-                            letter: data[y as usize][x as usize].letter,
-                            checked: -253,
-                            ceil_type: CeilType::Empty
-                        };
-                    }
-                }
-            }
+            let (removed, base_score, bonus_score) = Self::score_cells(&data, &bonus_lines);
+
+            let falls = Self::collapse(&mut data, &removed);
+            *self.last_clearing.borrow_mut() = Some(Clearing { removed, falls });
 
             if self.most_lengthy_word.borrow_mut().len() < word2.len() {
                 *self.most_lengthy_word.borrow_mut() = word2.clone();
             }
 
-            self.set_scores(scores + (scores_append * 2));
+            self.set_scores(scores + base_score + bonus_score);
 
-            if bonus_lines.len() > 0 {
-                sound::play_high_win();
-            } else {
-                sound::play_low_win();
-            }
+            let record = WordRecord {
+                word: word2,
+                path,
+                base_score,
+                bonus_score,
+                rarity_score: 0,
+                timestamp: now()
+            };
+
+            self.word_log.borrow_mut().push(record.clone());
+
+            outcome = CheckOutcome::Accepted(record);
         } else {
-            sound::play_wrong();
+            outcome = CheckOutcome::Wrong;
         }
 
         drop(data);
 
         let word = self.get_word();
         *self.is_word_ready.borrow_mut() = self.check_word(word.to_lowercase());
+
+        outcome
     }
 
     pub fn is_bonus(&self, x: i32, y: i32) -> bool {
@@ -384,8 +699,8 @@ impl  Field {
 
     pub fn is_bonus_exists(&self) -> bool {
 
-        for y in 0..self.height {
-            for x in 0..self.width {
+        for y in 0..self.get_height() {
+            for x in 0..self.get_width() {
                 if self.is_bonus(x,y) {
                     return true;
                 }
@@ -397,7 +712,7 @@ impl  Field {
 
     pub fn is_on_the_bonus_line(&self, _x: i32, y: i32) -> bool {
 
-        for nx in 0..self.width {
+        for nx in 0..self.get_width() {
             if matches!(self.get(nx,y).ceil_type, CeilType::Bonus) && self.is_bonus(nx,y) {
                 return true;
             }
@@ -407,8 +722,8 @@ impl  Field {
     }
 
     pub fn get_at_value(&self, val: i32) -> char {
-        for i in 0..self.height {
-            for j in 0..self.width {
+        for i in 0..self.get_height() {
+            for j in 0..self.get_width() {
                 if self.get(j,i).checked == val {
                     return self.get(j,i).letter;
                 }
@@ -418,6 +733,23 @@ impl  Field {
         'x'
     }
 
+    pub fn get_path(&self) -> Vec<(i32, i32)> {
+        let mut path = vec![];
+        let max = self.find_max();
+
+        for value in 1..max.0+1 {
+            for i in 0..self.get_height() {
+                for j in 0..self.get_width() {
+                    if self.get(j,i).checked == value {
+                        path.push((j, i));
+                    }
+                }
+            }
+        }
+
+        path
+    }
+
     pub fn get_word(&self) -> String {
         let mut value = String::from("");
         let max = self.find_max();
@@ -481,4 +813,72 @@ impl  Field {
 
         Direction::None
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ceil(letter: char, ceil_type: CeilType) -> Ceil {
+        Ceil { letter, checked: 0, ceil_type }
+    }
+
+    fn board(rows: &[&[(char, CeilType)]]) -> Field {
+        let field = Field::new(rows[0].len() as i32, rows.len() as i32);
+
+        field.set_board(rows.iter()
+            .map(|row| row.iter().map(|&(letter, ceil_type)| ceil(letter, ceil_type)).collect())
+            .collect());
+
+        field
+    }
+
+    // Выделяет слово по клеткам и возвращает предпросмотр и то, что дало его принятие
+    fn project_and_accept(field: &Field, path: &[(i32, i32)]) -> (WordProjection, WordRecord) {
+        for &(x, y) in path {
+            field.try_check(x, y);
+        }
+
+        let projection = field.project();
+        let (x, y) = path[path.len() - 1];
+
+        match field.try_check(x, y) {
+            CheckOutcome::Accepted(record) => (projection, record),
+            _ => panic!("word was not accepted")
+        }
+    }
+
+    #[test]
+    fn projects_plain_word() {
+        let field = board(&[
+            &[('А', CeilType::Active), ('Б', CeilType::Active), ('В', CeilType::Active)],
+            &[('К', CeilType::Active), ('О', CeilType::Active), ('Т', CeilType::Active)]
+        ]);
+        let (projection, record) = project_and_accept(&field, &[(0, 1), (1, 1), (2, 1)]);
+
+        assert!(projection.is_word);
+        assert!(!projection.is_bonus);
+        assert_eq!(projection.base_score, record.base_score);
+        assert_eq!(projection.bonus_score, record.bonus_score);
+        assert_eq!(projection.cleared, 3);
+        assert_eq!(field.get_scores(), projection.base_score + projection.bonus_score);
+    }
+
+    #[test]
+    fn projects_bonus_line() {
+        let field = board(&[
+            &[('А', CeilType::Active), ('Б', CeilType::Active), ('В', CeilType::Active), ('Г', CeilType::Active)],
+            &[('Д', CeilType::Bonus), (' ', CeilType::Empty), (' ', CeilType::Empty), ('Е', CeilType::Active)],
+            &[('К', CeilType::Active), ('О', CeilType::Active), ('Т', CeilType::Active), ('Ж', CeilType::Active)]
+        ]);
+        let (projection, record) = project_and_accept(&field, &[(0, 2), (1, 2), (2, 2)]);
+
+        assert!(projection.is_word);
+        assert!(projection.is_bonus);
+        assert!(projection.bonus_score > 0);
+        assert_eq!(projection.base_score, record.base_score);
+        assert_eq!(projection.bonus_score, record.bonus_score);
+        // Слово и две буквы бонусной строки, пустые клетки на ней не считаются
+        assert_eq!(projection.cleared, 5);
+        assert_eq!(field.get_scores(), projection.base_score + projection.bonus_score);
+    }
+}
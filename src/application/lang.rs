@@ -0,0 +1,517 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Serialize, Deserialize};
+
+// Язык интерфейса. Словарь игры остаётся русским при любом выборе
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    Russian,
+    English
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Russian, Language::English];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Language::Russian => "Русский",
+            Language::English => "English"
+        }
+    }
+}
+
+pub struct Strings {
+    pub title: &'static str,
+    pub scores: &'static str,
+    pub new_game_button: &'static str,
+    pub finish_button: &'static str,
+    pub leaders_title: &'static str,
+    pub stats_title: &'static str,
+    pub word_log_title: &'static str,
+    pub player_title: &'static str,
+    pub player: &'static str,
+    pub games_played: &'static str,
+    pub best_score: &'static str,
+    pub average_score: &'static str,
+    pub best_word: &'static str,
+    pub total_score: &'static str,
+    pub total_words: &'static str,
+    pub controls_title: &'static str,
+    pub controls_click: &'static str,
+    pub controls_drag: &'static str,
+    pub controls_cancel: &'static str,
+    pub controls_accept_click: &'static str,
+    pub controls_accept_drag: &'static str,
+    pub controls_finish: &'static str,
+    pub sort_score: &'static str,
+    pub sort_date: &'static str,
+    pub sort_duration: &'static str,
+    pub sort_words: &'static str,
+    pub words_short: &'static str,
+    pub filter_all: &'static str,
+    pub scoring_classic: &'static str,
+    pub scoring_rarity: &'static str,
+    pub menu_leaders: &'static str,
+    pub menu_stats: &'static str,
+    pub menu_word_log: &'static str,
+    pub menu_player: &'static str,
+    pub menu_scoring: &'static str,
+    pub menu_corpus: &'static str,
+    pub menu_export: &'static str,
+    pub menu_import: &'static str,
+    pub menu_verify: &'static str,
+    pub menu_preferences: &'static str,
+    pub menu_profile: &'static str,
+    pub menu_new_profile: &'static str,
+    pub yes: &'static str,
+    pub no: &'static str,
+    pub ok: &'static str,
+    pub cancel: &'static str,
+    pub close: &'static str,
+    pub from_start: &'static str,
+    pub continue_game: &'static str,
+    pub corpus_file: &'static str,
+    pub export_title: &'static str,
+    pub import_title: &'static str,
+    pub verify_title: &'static str,
+    pub player_name: &'static str,
+    pub summary_title: &'static str,
+    pub summary_score: &'static str,
+    pub summary_words: &'static str,
+    pub summary_longest_word: &'static str,
+    pub summary_rank: &'static str,
+    pub missed_words: &'static str,
+    pub new_game: &'static str,
+    pub selection_word: &'static str,
+    pub selection_not_word: &'static str,
+    pub selection_bonus: &'static str,
+    pub preview_empty: &'static str,
+    pub preview_scores: &'static str,
+    pub preview_rarity: &'static str,
+    pub preview_cleared: &'static str,
+    pub review_board: &'static str,
+    pub replay_title: &'static str,
+    pub replay_restart: &'static str,
+    pub replay_step: &'static str,
+    pub replay_play: &'static str,
+    pub replay_pause: &'static str,
+    pub verified: &'static str,
+    pub unverifiable: &'static str,
+    pub unverifiable_rarity: &'static str,
+    pub tampered: &'static str,
+    pub broken_replay: &'static str,
+    pub missing_seed: &'static str,
+    pub seed_mismatch: &'static str,
+    pub words_mismatch: &'static str,
+    pub word_mismatch: &'static str,
+    pub rarity_mismatch: &'static str,
+    pub score_mismatch: &'static str,
+    pub preferences_title: &'static str,
+    pub volume: &'static str,
+    pub mute: &'static str,
+    pub sound_events: &'static str,
+    pub sound_pack: &'static str,
+    pub builtin_sound_pack: &'static str,
+    pub sound_click: &'static str,
+    pub sound_undo: &'static str,
+    pub sound_wrong: &'static str,
+    pub sound_low_win: &'static str,
+    pub sound_high_win: &'static str,
+    pub animation_speed: &'static str,
+    pub reduced_motion: &'static str,
+    pub theme: &'static str,
+    pub theme_dark: &'static str,
+    pub theme_light: &'static str,
+    pub theme_high_contrast: &'static str,
+    pub theme_user: &'static str,
+    pub selection_mode: &'static str,
+    pub selection_click: &'static str,
+    pub selection_drag: &'static str,
+    pub scoring_profile: &'static str,
+    pub board_size: &'static str,
+    pub language: &'static str,
+    pub cannot_save: &'static str,
+    pub cannot_load: &'static str,
+    pub backup_restored: &'static str,
+    pub newer_save: &'static str,
+    pub newer_game_file: &'static str,
+    pub unversioned_game_file: &'static str,
+    pub defaults_restored: &'static str,
+    pub enter_leader_name: &'static str,
+    pub finish_game: &'static str,
+    pub player_exists: &'static str,
+    pub cannot_load_corpus: &'static str,
+    pub cannot_load_sound_pack: &'static str,
+    pub cannot_load_theme: &'static str,
+    pub cannot_export: &'static str,
+    pub cannot_import: &'static str,
+    pub no_replay: &'static str,
+    pub cannot_load_replay: &'static str,
+    pub import_game: &'static str,
+    pub cli_usage: &'static str,
+    pub cli_size_format: &'static str,
+    pub cli_size_range: &'static str,
+    pub cli_invalid_size: &'static str,
+    pub cli_missing_value: &'static str,
+    pub cli_one_argument: &'static str,
+    pub cli_invalid_seed: &'static str,
+    pub cli_unknown_option: &'static str,
+    pub cli_unknown_command: &'static str,
+    pub cli_import_usage: &'static str,
+    pub cli_game_file: &'static str,
+    pub cli_word: &'static str,
+    pub cli_file: &'static str,
+    pub cli_new_unfinished: &'static str,
+    pub cli_import_unfinished: &'static str,
+    pub cli_in_dictionary: &'static str,
+    pub cli_not_in_dictionary: &'static str
+}
+
+static RUSSIAN: Strings = Strings {
+    title: "Альфабит",
+    scores: "ОЧКИ",
+    new_game_button: "НОВАЯ ИГРА",
+    finish_button: "ФИНИШ",
+    leaders_title: "ТАБЛИЦА ЛИДЕРОВ",
+    stats_title: "СТАТИСТИКА СЛОВ",
+    word_log_title: "СЛОВА ЭТОЙ ИГРЫ",
+    player_title: "ПРОФИЛЬ ИГРОКА",
+    player: "ИГРОК",
+    games_played: "ИГР СЫГРАНО",
+    best_score: "ЛУЧШИЙ СЧЁТ",
+    average_score: "СРЕДНИЙ СЧЁТ",
+    best_word: "ЛУЧШЕЕ СЛОВО",
+    total_score: "ВСЕГО ОЧКОВ",
+    total_words: "ВСЕГО СЛОВ",
+    controls_title: "УПРАВЛЕНИЕ",
+    controls_click: "ЛЕВАЯ КН. МЫШИ - ВЫДЕЛЕНИЕ",
+    controls_drag: "ПРОТЯНУТЬ МЫШЬЮ - ВЫДЕЛЕНИЕ",
+    controls_cancel: "ПРАВАЯ КН. МЫШИ - ОТМЕНА",
+    controls_accept_click: "ПОВТОРНЫЙ КЛИК - ВЫБОР",
+    controls_accept_drag: "ОТПУСТИТЬ КНОПКУ - ВЫБОР",
+    controls_finish: "ФИНИШ - ЗАКОНЧИТЬ ИГРУ",
+    sort_score: "ОЧКИ",
+    sort_date: "ДАТА",
+    sort_duration: "ВРЕМЯ",
+    sort_words: "СЛОВА",
+    words_short: "сл.",
+    filter_all: "ВСЕ",
+    scoring_classic: "КЛАССИКА",
+    scoring_rarity: "РЕДКИЕ СЛОВА",
+    menu_leaders: "Таблица лидеров",
+    menu_stats: "Статистика слов",
+    menu_word_log: "Слова этой игры",
+    menu_player: "Профиль игрока",
+    menu_scoring: "Подсчёт очков",
+    menu_corpus: "Загрузить частотный словарь...",
+    menu_export: "Экспорт игры...",
+    menu_import: "Импорт игры...",
+    menu_verify: "Проверить таблицу лидеров",
+    menu_preferences: "Настройки...",
+    menu_profile: "Игрок",
+    menu_new_profile: "Новый игрок...",
+    yes: "Да",
+    no: "Нет",
+    ok: "Готово",
+    cancel: "Отмена",
+    close: "Закрыть",
+    from_start: "С начала",
+    continue_game: "Продолжить",
+    corpus_file: "Частотный словарь",
+    export_title: "Экспорт игры",
+    import_title: "Импорт игры",
+    verify_title: "Проверка таблицы лидеров, не совпадает",
+    player_name: "Имя игрока",
+    summary_title: "Итоги игры",
+    summary_score: "Очки",
+    summary_words: "Слов составлено",
+    summary_longest_word: "Самое длинное слово",
+    summary_rank: "Место в таблице лидеров",
+    missed_words: "Упущенные слова",
+    selection_word: "слово",
+    selection_not_word: "не слово",
+    selection_bonus: "бонус",
+    preview_empty: "Выделите слово на поле",
+    preview_scores: "Очки",
+    preview_rarity: "редкость",
+    preview_cleared: "Клеток",
+    new_game: "Новая игра",
+    review_board: "Посмотреть поле",
+    replay_title: "Запись игры",
+    replay_restart: "Сначала",
+    replay_step: "Шаг",
+    replay_play: "Пуск",
+    replay_pause: "Пауза",
+    verified: "подтверждён",
+    unverifiable: "нет записи",
+    unverifiable_rarity: "очки за редкость не проверяются",
+    tampered: "НЕ СОВПАДАЕТ",
+    broken_replay: "запись повреждена",
+    missing_seed: "нет зерна поля",
+    seed_mismatch: "поле не соответствует зерну",
+    words_mismatch: "слова не соответствуют ходам",
+    word_mismatch: "не соответствует ходам слово",
+    rarity_mismatch: "очки за редкость слова",
+    score_mismatch: "очков по ходам",
+    preferences_title: "Настройки",
+    volume: "Громкость",
+    mute: "Без звука (M)",
+    sound_events: "Озвучивать",
+    sound_pack: "Набор звуков",
+    builtin_sound_pack: "Встроенный",
+    sound_click: "Буква",
+    sound_undo: "Отмена",
+    sound_wrong: "Ошибка",
+    sound_low_win: "Слово",
+    sound_high_win: "Бонус",
+    animation_speed: "Скорость анимации",
+    reduced_motion: "Меньше движения",
+    theme: "Тема",
+    theme_dark: "Тёмная",
+    theme_light: "Светлая",
+    theme_high_contrast: "Контрастная",
+    theme_user: "Своя",
+    selection_mode: "Выделение",
+    selection_click: "Щелчками",
+    selection_drag: "Протягиванием",
+    scoring_profile: "Подсчёт очков",
+    board_size: "Поле (со следующей игры)",
+    language: "Язык",
+    cannot_save: "Не удалось сохранить настройки",
+    cannot_load: "Не удалось загрузить сохранение",
+    backup_restored: "Игра восстановлена из резервной копии",
+    newer_save: "сохранение сделано более новой версией игры",
+    newer_game_file: "файл сделан более новой версией игры",
+    unversioned_game_file: "в файле игры не указана версия",
+    defaults_restored: "Резервной копии нет, начата новая игра",
+    enter_leader_name: "Новый рекорд! Введите имя для таблицы лидеров",
+    finish_game: "Закончить игру?",
+    player_exists: "Игрок с таким именем уже есть",
+    cannot_load_corpus: "Не удалось загрузить частотный словарь",
+    cannot_load_sound_pack: "Не удалось загрузить набор звуков",
+    cannot_load_theme: "Не удалось загрузить тему",
+    cannot_export: "Не удалось экспортировать игру",
+    cannot_import: "Не удалось импортировать игру",
+    no_replay: "Для этой игры нет записи",
+    cannot_load_replay: "Запись игры повреждена",
+    import_game: "Начать загруженную игру с начала или продолжить с сохранённого места?",
+    cli_usage: "Использование: alphabit [--config ФАЙЛ | --portable] [команда]
+
+Параметры:
+  --config ФАЙЛ                         хранить сохранение в указанном файле
+  --portable                            хранить сохранение рядом с программой
+                                        (то же, что файл portable рядом с программой)
+
+Команды:
+  play                                  открыть игру (по умолчанию)
+  new [--seed N] [--size ШxВ] [--out ФАЙЛ] [--force]
+                                        новая игра; с --out поле записывается в файл,
+                                        иначе игра начинается в окне; начатая игра
+                                        заменяется только с --force
+  solve ФАЙЛ                            все слова на поле из файла игры
+  check СЛОВО                           есть ли слово в словаре
+  leaders                               таблица лидеров
+  verify                                проверка таблицы лидеров по записям игр
+  export ФАЙЛ                           сохранить текущую игру в файл
+  import ФАЙЛ [--fresh] [--force]       загрузить игру из файла, с --fresh - с начала;
+                                        начатая игра заменяется только с --force
+  reset                                 удалить сохранение, оставив резервную копию
+  help                                  эта справка",
+    cli_size_format: "размер поля задаётся как ШxВ",
+    cli_size_range: "допустимый размер поля",
+    cli_invalid_size: "неверный размер поля",
+    cli_missing_value: "не указано значение для",
+    cli_one_argument: "ожидается один аргумент",
+    cli_invalid_seed: "неверное зерно",
+    cli_unknown_option: "неизвестный параметр",
+    cli_unknown_command: "неизвестная команда",
+    cli_import_usage: "ожидается: import ФАЙЛ [--fresh] [--force]",
+    cli_game_file: "файл игры",
+    cli_word: "слово",
+    cli_file: "файл",
+    cli_new_unfinished: "текущая игра не закончена, для замены укажите --force или --out",
+    cli_import_unfinished: "текущая игра не закончена, для замены укажите --force",
+    cli_in_dictionary: "есть в словаре",
+    cli_not_in_dictionary: "нет в словаре"
+};
+
+static ENGLISH: Strings = Strings {
+    title: "Alphabit",
+    scores: "SCORE",
+    new_game_button: "NEW GAME",
+    finish_button: "FINISH",
+    leaders_title: "LEADERBOARD",
+    stats_title: "WORD STATISTICS",
+    word_log_title: "WORDS THIS GAME",
+    player_title: "PLAYER PROFILE",
+    player: "PLAYER",
+    games_played: "GAMES PLAYED",
+    best_score: "BEST SCORE",
+    average_score: "AVERAGE SCORE",
+    best_word: "BEST WORD",
+    total_score: "TOTAL SCORE",
+    total_words: "TOTAL WORDS",
+    controls_title: "CONTROLS",
+    controls_click: "LEFT CLICK - SELECT",
+    controls_drag: "DRAG - SELECT",
+    controls_cancel: "RIGHT CLICK - CANCEL",
+    controls_accept_click: "CLICK AGAIN - ACCEPT",
+    controls_accept_drag: "RELEASE - ACCEPT",
+    controls_finish: "FINISH - END THE GAME",
+    sort_score: "SCORE",
+    sort_date: "DATE",
+    sort_duration: "TIME",
+    sort_words: "WORDS",
+    words_short: "w.",
+    filter_all: "ALL",
+    scoring_classic: "CLASSIC",
+    scoring_rarity: "RARE WORDS",
+    menu_leaders: "Leaderboard",
+    menu_stats: "Word statistics",
+    menu_word_log: "Words this game",
+    menu_player: "Player profile",
+    menu_scoring: "Scoring",
+    menu_corpus: "Load word frequency list...",
+    menu_export: "Export game...",
+    menu_import: "Import game...",
+    menu_verify: "Verify leaderboard",
+    menu_preferences: "Preferences...",
+    menu_profile: "Player",
+    menu_new_profile: "New player...",
+    yes: "Yes",
+    no: "No",
+    ok: "Done",
+    cancel: "Cancel",
+    close: "Close",
+    from_start: "From the start",
+    continue_game: "Continue",
+    corpus_file: "Word frequency list",
+    export_title: "Export game",
+    import_title: "Import game",
+    verify_title: "Leaderboard verification, mismatches",
+    player_name: "Player name",
+    summary_title: "Game summary",
+    summary_score: "Score",
+    summary_words: "Words made",
+    summary_longest_word: "Longest word",
+    summary_rank: "Leaderboard place",
+    missed_words: "Missed words",
+    selection_word: "word",
+    selection_not_word: "not a word",
+    selection_bonus: "bonus",
+    preview_empty: "Select a word on the board",
+    preview_scores: "Points",
+    preview_rarity: "rarity",
+    preview_cleared: "Cells",
+    new_game: "New game",
+    review_board: "Review board",
+    replay_title: "Game replay",
+    replay_restart: "Restart",
+    replay_step: "Step",
+    replay_play: "Play",
+    replay_pause: "Pause",
+    verified: "verified",
+    unverifiable: "no replay",
+    unverifiable_rarity: "rarity points cannot be checked",
+    tampered: "MISMATCH",
+    broken_replay: "replay is damaged",
+    missing_seed: "board seed is missing",
+    seed_mismatch: "board does not match the seed",
+    words_mismatch: "words do not match the moves",
+    word_mismatch: "moves do not match the word",
+    rarity_mismatch: "rarity score of the word",
+    score_mismatch: "points by the moves",
+    preferences_title: "Preferences",
+    volume: "Volume",
+    mute: "Mute (M)",
+    sound_events: "Play sounds",
+    sound_pack: "Sound pack",
+    builtin_sound_pack: "Built-in",
+    sound_click: "Letter",
+    sound_undo: "Cancel",
+    sound_wrong: "Mistake",
+    sound_low_win: "Word",
+    sound_high_win: "Bonus",
+    animation_speed: "Animation speed",
+    reduced_motion: "Reduce motion",
+    theme: "Theme",
+    theme_dark: "Dark",
+    theme_light: "Light",
+    theme_high_contrast: "High contrast",
+    theme_user: "Custom",
+    selection_mode: "Selection",
+    selection_click: "By clicks",
+    selection_drag: "By dragging",
+    scoring_profile: "Scoring",
+    board_size: "Board (from next game)",
+    language: "Language",
+    cannot_save: "Could not save the settings",
+    cannot_load: "Could not load the save",
+    backup_restored: "The game was restored from the backup",
+    newer_save: "the save was made by a newer version of the game",
+    newer_game_file: "the file was made by a newer version of the game",
+    unversioned_game_file: "the game file has no version",
+    defaults_restored: "There is no backup, a new game was started",
+    enter_leader_name: "New record! Enter a name for the leaderboard",
+    finish_game: "Finish the game?",
+    player_exists: "A player with this name already exists",
+    cannot_load_corpus: "Could not load the word frequency list",
+    cannot_load_sound_pack: "Could not load the sound pack",
+    cannot_load_theme: "Could not load the theme",
+    cannot_export: "Could not export the game",
+    cannot_import: "Could not import the game",
+    no_replay: "There is no replay for this game",
+    cannot_load_replay: "The game replay is damaged",
+    import_game: "Start the imported game from the beginning or continue from the saved position?",
+    cli_usage: "Usage: alphabit [--config FILE | --portable] [command]
+
+Options:
+  --config FILE                         keep the save in the given file
+  --portable                            keep the save next to the program
+                                        (same as a portable file next to the program)
+
+Commands:
+  play                                  open the game (default)
+  new [--seed N] [--size WxH] [--out FILE] [--force]
+                                        new game; with --out the board is written to a file,
+                                        otherwise the game starts in the window; a started
+                                        game is replaced only with --force
+  solve FILE                            all words on the board from a game file
+  check WORD                            whether the word is in the dictionary
+  leaders                               leaderboard
+  verify                                check the leaderboard against game replays
+  export FILE                           save the current game to a file
+  import FILE [--fresh] [--force]       load a game from a file, with --fresh from the start;
+                                        a started game is replaced only with --force
+  reset                                 delete the save, keeping a backup
+  help                                  this help",
+    cli_size_format: "board size is given as WxH",
+    cli_size_range: "allowed board size",
+    cli_invalid_size: "invalid board size",
+    cli_missing_value: "missing value for",
+    cli_one_argument: "expected one argument",
+    cli_invalid_seed: "invalid seed",
+    cli_unknown_option: "unknown option",
+    cli_unknown_command: "unknown command",
+    cli_import_usage: "expected: import FILE [--fresh] [--force]",
+    cli_game_file: "game file",
+    cli_word: "word",
+    cli_file: "file",
+    cli_new_unfinished: "the current game is not finished, use --force or --out to replace it",
+    cli_import_unfinished: "the current game is not finished, use --force to replace it",
+    cli_in_dictionary: "in the dictionary",
+    cli_not_in_dictionary: "not in the dictionary"
+};
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn strings() -> &'static Strings {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => &ENGLISH,
+        _ => &RUSSIAN
+    }
+}
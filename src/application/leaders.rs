@@ -0,0 +1,245 @@
+use std::cmp::Reverse;
+use std::fmt;
+use serde::{Serialize, Deserialize, Deserializer, de};
+use crate::application::{lang, settings};
+use crate::application::replay::Replay;
+use crate::field::*;
+use crate::stats::*;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeaderRecord {
+    pub score: i32,
+    #[serde(default)]
+    pub player: String,
+    pub longest_word: String,
+    #[serde(default)]
+    pub date: u64,
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub words: usize,
+    #[serde(default)]
+    pub mode: GameMode,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub scoring_profile: ScoringProfile,
+    #[serde(default)]
+    pub replay: Option<Replay>
+}
+
+impl LeaderRecord {
+    pub fn from_field(field: &Field, player: &str, scoring_profile: ScoringProfile) -> Self {
+        LeaderRecord {
+            score: field.get_scores(),
+            player: player.to_string(),
+            longest_word: field.get_longest_word(),
+            date: now(),
+            duration: field.get_duration(),
+            words: field.get_word_log().len(),
+            mode: field.get_mode(),
+            seed: field.get_seed(),
+            scoring_profile,
+            replay: Replay::from_field(field)
+        }
+    }
+}
+
+// Старые сохранения хранили таблицу как список пар (очки, самое длинное слово)
+struct StoredLeaderRecord(LeaderRecord);
+
+impl<'de> Deserialize<'de> for StoredLeaderRecord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RecordVisitor;
+
+        impl<'de> de::Visitor<'de> for RecordVisitor {
+            type Value = StoredLeaderRecord;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("leader record or (score, word) pair")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let score: i32 = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let longest_word: String = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+                Ok(StoredLeaderRecord(LeaderRecord {
+                    score,
+                    player: String::new(),
+                    longest_word,
+                    date: 0,
+                    duration: 0,
+                    words: 0,
+                    mode: GameMode::default(),
+                    seed: 0,
+                    scoring_profile: ScoringProfile::default(),
+                    replay: None
+                }))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                LeaderRecord::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(StoredLeaderRecord)
+            }
+        }
+
+        deserializer.deserialize_any(RecordVisitor)
+    }
+}
+
+pub fn deserialize_leaders<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<LeaderRecord>, D::Error> {
+    let records = Vec::<StoredLeaderRecord>::deserialize(deserializer)?;
+
+    Ok(records.into_iter()
+        .map(|record| record.0)
+        .filter(|record| record.score > 0)
+        .collect())
+}
+
+// Место в таблице, которое займёт результат, если он в неё попадает
+pub fn get_rank(leaders: &[LeaderRecord], score: i32) -> Option<usize> {
+    if score <= 0 {
+        return None;
+    }
+
+    let idx = leaders.iter()
+        .position(|leader| leader.score < score)
+        .unwrap_or(leaders.len());
+
+    if idx >= settings::LEADERS_LIMIT {
+        return None;
+    }
+
+    Some(idx)
+}
+
+pub fn insert(leaders: &mut Vec<LeaderRecord>, record: LeaderRecord) -> Option<usize> {
+    let idx = get_rank(leaders, record.score)?;
+
+    leaders.insert(idx, record);
+    leaders.truncate(settings::LEADERS_LIMIT);
+
+    Some(idx)
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum LeadersSort {
+    Score,
+    Date,
+    Duration,
+    Words
+}
+
+impl LeadersSort {
+    pub fn next(self) -> Self {
+        match self {
+            LeadersSort::Score => LeadersSort::Date,
+            LeadersSort::Date => LeadersSort::Duration,
+            LeadersSort::Duration => LeadersSort::Words,
+            LeadersSort::Words => LeadersSort::Score
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            LeadersSort::Score => lang::strings().sort_score,
+            LeadersSort::Date => lang::strings().sort_date,
+            LeadersSort::Duration => lang::strings().sort_duration,
+            LeadersSort::Words => lang::strings().sort_words
+        }
+    }
+
+    pub fn column(&self, record: &LeaderRecord) -> String {
+        match self {
+            LeadersSort::Score | LeadersSort::Date => format_date(record.date),
+            LeadersSort::Duration => format_duration(record.duration),
+            LeadersSort::Words => format!("{} {}", record.words, lang::strings().words_short)
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum LeadersFilter {
+    All,
+    Scoring(ScoringProfile),
+    Player(String)
+}
+
+impl LeadersFilter {
+    pub fn next(&self, players: &[String]) -> Self {
+        match self {
+            LeadersFilter::All => LeadersFilter::Scoring(ScoringProfile::Classic),
+            LeadersFilter::Scoring(ScoringProfile::Classic) => LeadersFilter::Scoring(ScoringProfile::Rarity),
+            LeadersFilter::Scoring(ScoringProfile::Rarity) => match players.first() {
+                Some(player) => LeadersFilter::Player(player.clone()),
+                None => LeadersFilter::All
+            },
+            LeadersFilter::Player(current) => {
+                let next = players.iter()
+                    .position(|player| player == current)
+                    .and_then(|idx| players.get(idx + 1));
+
+                match next {
+                    Some(player) => LeadersFilter::Player(player.clone()),
+                    None => LeadersFilter::All
+                }
+            }
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            LeadersFilter::All => String::from(lang::strings().filter_all),
+            LeadersFilter::Scoring(profile) => profile.title().to_string(),
+            LeadersFilter::Player(player) => player.to_uppercase()
+        }
+    }
+
+    fn matches(&self, record: &LeaderRecord) -> bool {
+        match self {
+            LeadersFilter::All => true,
+            LeadersFilter::Scoring(profile) => record.scoring_profile == *profile,
+            LeadersFilter::Player(player) => record.player == *player
+        }
+    }
+}
+
+pub fn select<'a>(leaders: &'a [LeaderRecord], sort: LeadersSort, filter: &LeadersFilter) -> Vec<&'a LeaderRecord> {
+    let mut selected: Vec<&LeaderRecord> = leaders.iter()
+        .filter(|record| filter.matches(record))
+        .collect();
+
+    match sort {
+        LeadersSort::Score => selected.sort_by_key(|record| Reverse(record.score)),
+        LeadersSort::Date => selected.sort_by_key(|record| Reverse(record.date)),
+        LeadersSort::Duration => selected.sort_by_key(|record| record.duration),
+        LeadersSort::Words => selected.sort_by_key(|record| Reverse(record.words))
+    }
+
+    selected
+}
+
+pub fn format_duration(seconds: u64) -> String {
+    format!("{}:{:0>2}", seconds / 60, seconds % 60)
+}
+
+// Дата по UTC в виде ДД.ММ.ГГ, 0 - дата неизвестна
+pub fn format_date(timestamp: u64) -> String {
+    if timestamp == 0 {
+        return String::from("-");
+    }
+
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:0>2}.{:0>2}.{:0>2}", day, month, year % 100)
+}
@@ -3,50 +3,270 @@ pub(crate) mod settings;
 pub(crate) mod ui;
 pub(crate) mod field;
 pub(crate) mod sound;
-
-use std::cell::RefCell;
+pub(crate) mod stats;
+pub(crate) mod leaders;
+pub(crate) mod profiles;
+pub(crate) mod dictionary;
+pub(crate) mod solver;
+pub(crate) mod storage;
+pub(crate) mod save;
+pub(crate) mod replay;
+pub(crate) mod verify;
+pub(crate) mod cli;
+pub(crate) mod lang;
+pub(crate) mod preferences;
+pub(crate) mod theme;
+pub(crate) mod effects;
+
+use std::cell::{Cell, RefCell};
 use fltk::{app, prelude::*, *, window::DoubleWindow};
 use fltk::window::Window;
 use fltk::enums::{Event};
 use std::rc::Rc;
-use serde::{Serialize, Deserialize};
 use fltk::app::MouseButton;
 use settings::*;
 use ui::*;
 use field::*;
-use animation::*;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use stats::*;
+use leaders::*;
+use profiles::*;
+use storage::*;
+use save::*;
+use preferences::{Preferences, SelectionMode, ThemeName};
+
+#[derive(Debug, Clone)]
 struct Config {
-    field: Field,
+    profiles: Vec<Profile>,
+    active_profile: usize,
     position_x: i32,
     position_y: i32,
-    table_of_leaders: Vec<(i32, String)>
+    table_of_leaders: Vec<LeaderRecord>,
+    corpus_path: Option<String>,
+    scoring_profile: ScoringProfile,
+    last_player_name: String,
+    preferences: Preferences,
+    sound_pack: Option<String>,
+    // Имя файла темы, когда выбрана тема пользователя
+    user_theme: Option<String>
 }
 
-impl ::std::default::Default for Config {
-    fn default() -> Self {
+impl Config {
+    fn from_save(save: SaveGame) -> Result<Self, StorageError> {
+        let mut config = Config {
+            profiles: save.profiles.iter()
+                .map(SavedProfile::to_profile)
+                .collect::<Result<Vec<Profile>, StorageError>>()?,
+            active_profile: save.active_profile,
+            position_x: save.position_x,
+            position_y: save.position_y,
+            table_of_leaders: save.leaders,
+            corpus_path: save.corpus_path,
+            scoring_profile: save.scoring_profile,
+            last_player_name: save.last_player_name,
+            preferences: save.preferences.normalized(),
+            sound_pack: save.sound_pack,
+            user_theme: save.user_theme
+        };
+
+        if config.profiles.is_empty() {
+            config.profiles.push(Profile::new(DEFAULT_PLAYER_NAME));
+        }
+
+        if config.active_profile >= config.profiles.len() {
+            config.active_profile = 0;
+        }
+
+        Ok(config)
+    }
+
+    fn to_save(&self) -> SaveGame {
+        SaveGame {
+            version: SAVE_VERSION,
+            profiles: self.profiles.iter().map(SavedProfile::from_profile).collect(),
+            active_profile: self.active_profile,
+            position_x: self.position_x,
+            position_y: self.position_y,
+            leaders: self.table_of_leaders.clone(),
+            corpus_path: self.corpus_path.clone(),
+            scoring_profile: self.scoring_profile,
+            last_player_name: self.last_player_name.clone(),
+            preferences: self.preferences,
+            sound_pack: self.sound_pack.clone(),
+            user_theme: self.user_theme.clone()
+        }
+    }
+
+    fn get_profile(&self) -> &Profile {
+        &self.profiles[self.active_profile]
+    }
+
+    fn get_players(&self) -> Vec<String> {
+        self.profiles.iter().map(|profile| profile.name.clone()).collect()
+    }
+
+    fn sync(&mut self, field: &Field, word_stats: &WordStats, player_stats: &PlayerStats) {
+        let profile = &mut self.profiles[self.active_profile];
+        profile.field = field.clone();
+        profile.word_stats = word_stats.clone();
+        profile.stats = player_stats.clone();
+    }
+
+    fn save(&mut self, field: &Field, word_stats: &WordStats, player_stats: &PlayerStats, scheduler: &mut SaveScheduler<SaveGame>) {
+        self.sync(field, word_stats, player_stats);
+        scheduler.flush(self.to_save());
+    }
+}
+
+// Событие, по которому окно сбрасывает накопленные изменения на диск
+const SAVE_EVENT: Event = Event::from_i32(40);
+
+// Ошибка сохранения не должна ронять игру, о ней сообщается в окне
+fn report(notice: &RefCell<Option<String>>, result: Result<(), StorageError>) {
+    *notice.borrow_mut() = result.err()
+        .map(|err| format!("{}: {}", lang::strings().cannot_save, err));
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum SidebarView {
+    Leaders,
+    Stats,
+    WordLog,
+    Player
+}
+
+#[derive(Copy, Clone)]
+enum MenuAction {
+    ShowLeaders,
+    ShowStats,
+    ShowWordLog,
+    ShowPlayer,
+    ToggleScoringProfile,
+    Preferences,
+    LoadCorpus,
+    ExportGame,
+    ImportGame,
+    VerifyLeaders,
+    SwitchProfile(usize),
+    NewProfile
+}
 
-        let field = Field::new(WIDTH, HEIGHT);
+fn menu_items(config: &Config) -> Vec<(String, MenuAction)> {
+    let strings = lang::strings();
+    let mut items = vec![
+        (String::from(strings.menu_leaders), MenuAction::ShowLeaders),
+        (String::from(strings.menu_stats), MenuAction::ShowStats),
+        (String::from(strings.menu_word_log), MenuAction::ShowWordLog),
+        (String::from(strings.menu_player), MenuAction::ShowPlayer),
+        (format!("{}: {}", strings.menu_scoring, config.scoring_profile.title()), MenuAction::ToggleScoringProfile),
+        (String::from(strings.menu_preferences), MenuAction::Preferences),
+        (String::from(strings.menu_corpus), MenuAction::LoadCorpus),
+        (String::from(strings.menu_export), MenuAction::ExportGame),
+        (String::from(strings.menu_import), MenuAction::ImportGame),
+        (String::from(strings.menu_verify), MenuAction::VerifyLeaders)
+    ];
+
+    for (idx, profile) in config.profiles.iter().enumerate() {
+        let mark = if idx == config.active_profile { "●" } else { "○" };
+        items.push((format!("{} {}: {}", mark, strings.menu_profile, profile.name), MenuAction::SwitchProfile(idx)));
+    }
+
+    items.push((String::from(strings.menu_new_profile), MenuAction::NewProfile));
+
+    items
+}
+
+fn window_title(player: &str) -> String {
+    format!("{} - {}", lang::strings().title, player)
+}
+
+// Окно подстраивается под размер поля текущей игры
+fn fit_window(wind: &mut Window, field: &Field) {
+    set_board_size(field.get_width(), field.get_height());
+
+    let (width, height) = window_size();
+    if wind.w() != width || wind.h() != height {
+        wind.set_size(width, height);
+    }
+}
 
-        field.generate();
+// Размер поля из настроек вступает в силу с новой игры
+fn start_new_game(field: &Field) {
+    let (width, height) = preferences::current().board_size();
+
+    if (field.get_width(), field.get_height()) != (width, height) {
+        field.resize(width, height);
+    }
+
+    field.new_game();
+}
+
+fn toggle_mute(config: &mut Config) {
+    config.preferences.muted = !config.preferences.muted;
+    config.preferences.apply();
+}
+
+// Серия слов подряд без ошибок и отмен озвучивается, если в наборе звуков есть звук серии
+fn update_streak(streak: &mut u32, outcome: &CheckOutcome) {
+    match outcome {
+        CheckOutcome::Accepted(_) => {
+            *streak += 1;
+            if streak.is_multiple_of(STREAK_WORDS) {
+                sound::play(sound::Sound::Streak);
+            }
+        },
+        CheckOutcome::Wrong => *streak = 0,
+        CheckOutcome::Selected | CheckOutcome::Ignored => {}
+    }
+}
+
+// Очки за редкость и статистика для только что принятого слова
+// Таймер переливания заводится, когда на поле появляется что переливать, и останавливается вместе с ним.
+// Во время переходов окно и так обновляется каждый кадр
+fn arm_pulse(wind: &Window, field: &Rc<Field>, is_armed: &Rc<Cell<bool>>) {
+    if is_armed.get() || !is_pulsing(field) {
+        return;
+    }
 
+    is_armed.set(true);
+
+    let mut wind = wind.clone();
+    let field = Rc::clone(field);
+    let is_armed = Rc::clone(is_armed);
+
+    app::add_timeout3(PULSE_INTERVAL, move |handle| {
+        if !is_pulsing(&field) {
+            is_armed.set(false);
+            return;
+        }
+
+        if !is_animating() {
+            wind.redraw();
+        }
+
+        app::repeat_timeout3(PULSE_INTERVAL, handle);
+    });
+}
+
+fn reward_word(field: &Field, word_stats: &RefCell<WordStats>, scoring_profile: ScoringProfile, record: &WordRecord) {
+    let mut word_stats = word_stats.borrow_mut();
+    field.reward_last_word(word_stats.rarity_bonus(&record.word, scoring_profile));
+    word_stats.record(&record.word);
+}
+
+impl ::std::default::Default for Config {
+    fn default() -> Self {
         Self {
-            field: field,
+            profiles: vec![Profile::new(DEFAULT_PLAYER_NAME)],
+            active_profile: 0,
             position_x: 0,
             position_y: 0,
-            table_of_leaders: vec![
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-                (0, String::from("-")),
-            ]
+            table_of_leaders: vec![],
+            corpus_path: None,
+            scoring_profile: ScoringProfile::default(),
+            last_player_name: String::new(),
+            preferences: Preferences::default(),
+            sound_pack: None,
+            user_theme: None
         }
     }
 }
@@ -59,84 +279,474 @@ fn load_icon(wind: &mut DoubleWindow) {
     wind.set_icon(Some(image));
 }
 
-pub fn app() -> Result<(), confy::ConfyError> {
+fn load_config() -> Loaded<Config> {
+    storage::load(|content| Config::from_save(save::parse(content)?))
+}
 
-    let mut config: Config = confy::load(SETTINGS_NAME, None)?;
+pub fn app() -> Result<(), FltkError> {
 
     let app = app::App::default();
 
-    let field = Rc::new(config.field.clone());
+    let loaded = load_config();
+    let mut config = loaded.value;
+
+    config.preferences.apply();
+    sound::init();
+
+    if let Some(pack) = &config.sound_pack {
+        if let Err(err) = sound::set_pack(Some(pack)) {
+            dialog::alert_default(&format!("{}: {}", lang::strings().cannot_load_sound_pack, err));
+            config.sound_pack = None;
+        }
+    }
+
+    match theme::load(config.preferences.theme, config.user_theme.as_deref()) {
+        Ok(loaded) => theme::set(loaded),
+        Err(err) => {
+            dialog::alert_default(&format!("{}: {}", lang::strings().cannot_load_theme, err));
+            config.preferences.theme = ThemeName::default();
+            config.user_theme = None;
+        }
+    }
+
+    if let Some(warning) = loaded.warning {
+        dialog::alert_default(&warning);
+    }
+
+    let scheduler: Rc<RefCell<SaveScheduler<SaveGame>>> = Rc::new(RefCell::new(SaveScheduler::new()));
+    let scheduler_idle = Rc::clone(&scheduler);
+    let scheduler_exit = Rc::clone(&scheduler);
+
+    let notice: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    let notice_draw = Rc::clone(&notice);
+
+    let field = Rc::new(config.get_profile().field.clone());
     let field_draw = Rc::clone(&field);
+    let board_view = Rc::new(BoardView::default());
+    let board_view_draw = Rc::clone(&board_view);
+    let is_pulse_armed = Rc::new(Cell::new(false));
     let table_of_leaders = Rc::new(RefCell::new(config.table_of_leaders.clone()));
     let table_of_leaders_draw = Rc::clone(&table_of_leaders);
 
+    let mut word_stats = config.get_profile().word_stats.clone();
+    if let Some(path) = &config.corpus_path {
+        if word_stats.load_corpus(path).is_err() {
+            config.corpus_path = None;
+        }
+    }
+    let word_stats = Rc::new(RefCell::new(word_stats));
+    let word_stats_draw = Rc::clone(&word_stats);
+    let scoring_profile = Rc::new(Cell::new(config.scoring_profile));
+    let scoring_profile_draw = Rc::clone(&scoring_profile);
+
+    let player_name = Rc::new(RefCell::new(config.get_profile().name.clone()));
+    let player_name_draw = Rc::clone(&player_name);
+    let player_stats = Rc::new(RefCell::new(config.get_profile().stats.clone()));
+    let player_stats_draw = Rc::clone(&player_stats);
+
+    let sidebar_view = Rc::new(Cell::new(SidebarView::Leaders));
+    let sidebar_view_draw = Rc::clone(&sidebar_view);
+    let sidebar_scroll = Rc::new(Cell::new(0usize));
+    let sidebar_scroll_draw = Rc::clone(&sidebar_scroll);
+    let leaders_sort = Rc::new(Cell::new(LeadersSort::Score));
+    let leaders_sort_draw = Rc::clone(&leaders_sort);
+    let leaders_filter = Rc::new(RefCell::new(LeadersFilter::All));
+    let leaders_filter_draw = Rc::clone(&leaders_filter);
+
+    set_board_size(field.get_width(), field.get_height());
+    let (width, height) = window_size();
+
     let mut wind = Window::new(
         config.position_x,
         config.position_y,
-        WIDTH * CELL_SIZE + SIDEBAR_WIDTH,
-        HEIGHT * CELL_SIZE + OFFSET_Y,
-        TITLE
+        width,
+        height,
+        None
     );
 
+    wind.set_label(&window_title(&player_name.borrow()));
+
     load_icon(&mut wind);
 
+    // Слова подряд без ошибок и отмен
+    let mut streak = 0;
+
     wind.handle({
         move |f, ev| {
             match ev {
                 Event::Push => {
 
-                    if field.is_blocked() {
+                    if board_view.is_clearing() {
                         return true;
                     }
 
                     let (x,y) = app::event_coords();
 
+                    if y > 5 && y < OFFSET_Y - 10 && x > 455 && x < 600 && field.is_finished() {
+                        start_new_game(&field);
+                        fit_window(f, &field);
+                        sidebar_scroll.set(0);
+
+                        scheduler.borrow_mut().mark_dirty();
+
+                        f.redraw();
+
+                        return true;
+                    }
+
                     if y > 5 && y < OFFSET_Y - 10 && x > 455 && x < 600 {
 
-                        let mut leaders = (*table_of_leaders).borrow_mut();
-                        for (idx, leader) in leaders.iter().enumerate() {
-                            if leader.0 < field.get_scores() {
-                                leaders.insert(
-                                    idx,
-                                    (field.get_scores(), field.get_longest_word()
-                                    )
-                                );
-                                break;
-                            }
-                        }
+                        let is_confirmed = dialog::choice2_default(lang::strings().finish_game, lang::strings().no, lang::strings().yes, "") == Some(1);
 
-                        if leaders.len() > 10 {
-                            leaders.pop();
+                        if !is_confirmed {
+                            return true;
                         }
 
-                        field.generate();
-                        field.set_longest_word(String::new());
-                        field.set_scores(0);
+                        let rank = leaders::get_rank(&table_of_leaders.borrow(), field.get_scores());
+
+                        if rank.is_some() {
+                            let default_name = if config.last_player_name.is_empty() {
+                                player_name.borrow().clone()
+                            } else {
+                                config.last_player_name.clone()
+                            };
+
+                            let name = dialog::input_default(lang::strings().enter_leader_name, &default_name)
+                                .map(|name| name.trim().to_string())
+                                .filter(|name| !name.is_empty())
+                                .unwrap_or(default_name);
 
-                        config.field = (*field).clone();
-                        config.table_of_leaders = leaders.clone();
+                            leaders::insert(
+                                &mut table_of_leaders.borrow_mut(),
+                                LeaderRecord::from_field(&field, &name, config.scoring_profile)
+                            );
 
-                        confy::store(SETTINGS_NAME, None, &config)
-                            .expect(CANNOT_SAVE_MSG);
+                            config.last_player_name = name;
+                        }
+
+                        player_stats.borrow_mut().record_game(&field);
+                        field.set_finished(true);
+
+                        config.table_of_leaders = table_of_leaders.borrow().clone();
+                        config.save(&field, &word_stats.borrow(), &player_stats.borrow(), &mut scheduler.borrow_mut());
+
+                        let summary = GameSummary {
+                            score: field.get_scores(),
+                            words: field.get_word_log().len(),
+                            longest_word: field.get_longest_word(),
+                            rank,
+                            missed: solver::solve(&field)
+                        };
+
+                        if let SummaryAction::NewGame = show_summary(&summary) {
+                            start_new_game(&field);
+                            fit_window(f, &field);
+                            sidebar_scroll.set(0);
+                            scheduler.borrow_mut().mark_dirty();
+                        }
 
                         f.redraw();
+
+                        return true;
                     }
 
-                    if y < OFFSET_Y {
+                    if x >= sidebar_x() {
+                        if y < CELL_SIZE && x >= sidebar_x() + SIDEBAR_WIDTH - SOUND_TOGGLE_WIDTH {
+                            toggle_mute(&mut config);
+                            scheduler.borrow_mut().mark_dirty();
+
+                            f.redraw();
+                        } else if y < CELL_SIZE {
+                            let items = menu_items(&config);
+                            let labels: Vec<String> = items.iter()
+                                .map(|(label, _)| label.clone())
+                                .collect();
+
+                            match popup_menu(&labels).map(|idx| items[idx].1) {
+                                Some(MenuAction::ShowLeaders) => {
+                                    sidebar_view.set(SidebarView::Leaders);
+                                    sidebar_scroll.set(0);
+                                },
+                                Some(MenuAction::ShowStats) => {
+                                    sidebar_view.set(SidebarView::Stats);
+                                    sidebar_scroll.set(0);
+                                },
+                                Some(MenuAction::ShowWordLog) => {
+                                    sidebar_view.set(SidebarView::WordLog);
+                                    let log_size = field.get_word_log().len();
+                                    sidebar_scroll.set(log_size.saturating_sub(SIDEBAR_ROWS as usize));
+                                },
+                                Some(MenuAction::ShowPlayer) => {
+                                    sidebar_view.set(SidebarView::Player);
+                                    sidebar_scroll.set(0);
+                                },
+                                Some(MenuAction::ToggleScoringProfile) => {
+                                    config.scoring_profile = match config.scoring_profile {
+                                        ScoringProfile::Classic => ScoringProfile::Rarity,
+                                        ScoringProfile::Rarity => ScoringProfile::Classic
+                                    };
+                                    scoring_profile.set(config.scoring_profile);
+                                },
+                                Some(MenuAction::Preferences) => {
+                                    let chosen = show_preferences(PreferencesForm {
+                                        preferences: config.preferences,
+                                        scoring_profile: config.scoring_profile,
+                                        sound_pack: config.sound_pack.clone(),
+                                        user_theme: config.user_theme.clone()
+                                    });
+
+                                    if let Some(form) = chosen {
+                                        config.preferences = form.preferences;
+                                        config.scoring_profile = form.scoring_profile;
+                                        scoring_profile.set(config.scoring_profile);
+                                        config.sound_pack = form.sound_pack;
+                                        config.user_theme = form.user_theme;
+                                    }
+                                    f.set_label(&window_title(&player_name.borrow()));
+                                },
+                                Some(MenuAction::LoadCorpus) => {
+                                    if let Some(path) = choose_file(lang::strings().corpus_file) {
+                                        let path = path.to_string_lossy().to_string();
+                                        if word_stats.borrow_mut().load_corpus(&path).is_ok() {
+                                            config.corpus_path = Some(path);
+                                        } else {
+                                            dialog::alert_default(lang::strings().cannot_load_corpus);
+                                        }
+                                    }
+                                },
+                                Some(MenuAction::ExportGame) => {
+                                    let preset = format!("alphabit-{}.ron", field.get_seed());
+                                    if let Some(path) = choose_save_file(lang::strings().export_title, &preset) {
+                                        if let Err(err) = save::export_game(&field, &path) {
+                                            dialog::alert_default(&format!("{}: {}", lang::strings().cannot_export, err));
+                                        }
+                                    }
+                                },
+                                Some(MenuAction::ImportGame) => {
+                                    if let Some(path) = choose_file(lang::strings().import_title) {
+                                        match save::import_game(&path) {
+                                            Ok(imported) => {
+                                                let strings = lang::strings();
+                                                match dialog::choice2_default(strings.import_game, strings.cancel, strings.from_start, strings.continue_game) {
+                                                    Some(1) => {
+                                                        field.assign(&imported);
+                                                        field.restart();
+                                                    },
+                                                    Some(2) => field.assign(&imported),
+                                                    _ => {}
+                                                }
+                                                sidebar_scroll.set(0);
+                                            },
+                                            Err(err) => {
+                                                dialog::alert_default(&format!("{}: {}", lang::strings().cannot_import, err));
+                                            }
+                                        }
+                                    }
+                                },
+                                Some(MenuAction::VerifyLeaders) => {
+                                    let (lines, tampered) = verify::report(&table_of_leaders.borrow());
+                                    show_report(&format!("{}: {}", lang::strings().verify_title, tampered), &lines);
+                                },
+                                Some(MenuAction::SwitchProfile(idx)) => {
+                                    config.sync(&field, &word_stats.borrow(), &player_stats.borrow());
+                                    config.active_profile = idx;
+                                },
+                                Some(MenuAction::NewProfile) => {
+                                    let name = dialog::input_default(lang::strings().player_name, "")
+                                        .map(|name| name.trim().to_string())
+                                        .unwrap_or_default();
+
+                                    if config.get_players().contains(&name) {
+                                        dialog::alert_default(lang::strings().player_exists);
+                                    } else if !name.is_empty() {
+                                        config.sync(&field, &word_stats.borrow(), &player_stats.borrow());
+                                        config.profiles.push(Profile::new(&name));
+                                        config.active_profile = config.profiles.len() - 1;
+                                    }
+                                },
+                                None => {}
+                            }
+
+                            let profile = config.get_profile();
+                            if *player_name.borrow() != profile.name {
+                                field.assign(&profile.field);
+                                word_stats.borrow_mut().assign_played(&profile.word_stats);
+                                *player_stats.borrow_mut() = profile.stats.clone();
+                                *player_name.borrow_mut() = profile.name.clone();
+                                f.set_label(&window_title(&profile.name));
+                                sidebar_scroll.set(0);
+                            }
+
+                            fit_window(f, &field);
+
+                            scheduler.borrow_mut().mark_dirty();
+
+                            f.redraw();
+                        } else if (CELL_SIZE..CELL_SIZE * (SIDEBAR_ROWS + 1)).contains(&y) &&
+                            sidebar_view.get() == SidebarView::Leaders {
+                            let idx = sidebar_scroll.get() + (y / CELL_SIZE - 1) as usize;
+                            // Копия, чтобы таблица не оставалась занятой, пока открыто окно записи
+                            let record = leaders::select(
+                                &table_of_leaders.borrow(),
+                                leaders_sort.get(),
+                                &leaders_filter.borrow()
+                            ).get(idx).map(|record| (*record).clone());
+
+                            if let Some(record) = record {
+                                match &record.replay {
+                                    Some(replay) => match replay.to_field() {
+                                        Ok(start) => show_replay(
+                                            replay,
+                                            start,
+                                            &format!("{}: {} - {}", lang::strings().replay_title, record.player, record.score)
+                                        ),
+                                        Err(_) => dialog::alert_default(lang::strings().cannot_load_replay)
+                                    },
+                                    None => dialog::alert_default(lang::strings().no_replay)
+                                }
+                            }
+                        } else if (CELL_SIZE * 11..CELL_SIZE * 12).contains(&y) &&
+                            sidebar_view.get() == SidebarView::Leaders {
+                            if x < sidebar_x() + SIDEBAR_WIDTH / 2 {
+                                leaders_sort.set(leaders_sort.get().next());
+                            } else {
+                                let next = leaders_filter.borrow().next(&config.get_players());
+                                *leaders_filter.borrow_mut() = next;
+                            }
+                            sidebar_scroll.set(0);
+
+                            f.redraw();
+                        }
+
+                        return true;
+                    }
+
+                    if y < OFFSET_Y || field.is_finished() {
                         return true;
                     }
 
                     let (cell_x, cell_y) = (x / CELL_SIZE, (y - OFFSET_Y) / CELL_SIZE);
 
+                    if cell_x >= field.get_width() || cell_y >= field.get_height() {
+                        return true;
+                    }
+
                     if app::event_mouse_button() == MouseButton::Right {
                         field.deselect();
+                        sound::play_undo();
+                        streak = 0;
                     } else {
-                        field.try_check(cell_x, cell_y);
+                        // При выделении протягиванием каждое нажатие начинает новое слово
+                        if preferences::current().selection_mode == SelectionMode::Drag && field.find_max().0 > 0 {
+                            field.deselect();
+                        }
+
+                        let outcome = field.try_check(cell_x, cell_y);
+                        sound::play_outcome(&outcome, &field);
+                        update_streak(&mut streak, &outcome);
+
+                        if let CheckOutcome::Accepted(record) = outcome {
+                            reward_word(&field, &word_stats, config.scoring_profile, &record);
+
+                            let log_size = field.get_word_log().len();
+                            sidebar_scroll.set(log_size.saturating_sub(SIDEBAR_ROWS as usize));
+                        }
+                    }
+
+                    scheduler.borrow_mut().mark_dirty();
+
+                    f.redraw();
+
+                    true
+                },
+                Event::Drag => {
+                    if preferences::current().selection_mode != SelectionMode::Drag ||
+                        app::event_mouse_button() != MouseButton::Left ||
+                        board_view.is_clearing() || field.is_finished() {
+                        return false;
+                    }
+
+                    let (x, y) = app::event_coords();
+
+                    if x < 0 || y < OFFSET_Y {
+                        return true;
+                    }
+
+                    let (cell_x, cell_y) = (x / CELL_SIZE, (y - OFFSET_Y) / CELL_SIZE);
+
+                    if cell_x < field.get_width() && cell_y < field.get_height() && field.can_extend(cell_x, cell_y) {
+                        sound::play_outcome(&field.try_check(cell_x, cell_y), &field);
+                        scheduler.borrow_mut().mark_dirty();
+                        f.redraw();
+                    }
+
+                    true
+                },
+                Event::Released => {
+                    let (max_val, max_x, max_y) = field.find_max();
+
+                    if preferences::current().selection_mode != SelectionMode::Drag ||
+                        app::event_mouse_button() != MouseButton::Left || max_val == 0 {
+                        return false;
+                    }
+
+                    // Повторный выбор последней буквы принимает слово так же, как при выделении щелчками
+                    if field.is_word() {
+                        let outcome = field.try_check(max_x, max_y);
+                        sound::play_outcome(&outcome, &field);
+                        update_streak(&mut streak, &outcome);
+
+                        if let CheckOutcome::Accepted(record) = outcome {
+                            reward_word(&field, &word_stats, config.scoring_profile, &record);
+
+                            let log_size = field.get_word_log().len();
+                            sidebar_scroll.set(log_size.saturating_sub(SIDEBAR_ROWS as usize));
+                        }
+                    } else {
+                        field.deselect();
+                        sound::play_undo();
+                        streak = 0;
                     }
 
-                    config.field = (*field).clone();
-                    confy::store(SETTINGS_NAME, None, &config)
-                        .expect(CANNOT_SAVE_MSG);
+                    scheduler.borrow_mut().mark_dirty();
+
+                    f.redraw();
+
+                    true
+                },
+                // Клавиша M в любой раскладке
+                Event::KeyDown if matches!(app::event_text().to_lowercase().as_str(), "m" | "ь") => {
+                    toggle_mute(&mut config);
+                    scheduler.borrow_mut().mark_dirty();
+
+                    f.redraw();
+
+                    true
+                },
+                Event::MouseWheel => {
+                    let (x, _) = app::event_coords();
+
+                    if x < sidebar_x() {
+                        return false;
+                    }
+
+                    let rows = match sidebar_view.get() {
+                        SidebarView::Leaders => leaders::select(
+                            &table_of_leaders.borrow(),
+                            leaders_sort.get(),
+                            &leaders_filter.borrow()
+                        ).len(),
+                        SidebarView::WordLog => field.get_word_log().len(),
+                        SidebarView::Stats | SidebarView::Player => return false
+                    };
+                    let max_scroll = rows.saturating_sub(SIDEBAR_ROWS as usize);
+
+                    // MouseWheel::Up в fltk соответствует прокрутке колеса на себя
+                    match app::event_dy() {
+                        app::MouseWheel::Up => sidebar_scroll.set((sidebar_scroll.get() + 1).min(max_scroll)),
+                        app::MouseWheel::Down => sidebar_scroll.set(sidebar_scroll.get().saturating_sub(1)),
+                        _ => {}
+                    }
 
                     f.redraw();
 
@@ -144,11 +754,17 @@ pub fn app() -> Result<(), confy::ConfyError> {
                 },
                 Event::NoEvent => {
 
-                    if config.position_x != f.x() || config.position_x != f.y() {
+                    if config.position_x != f.x() || config.position_y != f.y() {
                         config.position_x = f.x();
                         config.position_y = f.y();
-                        confy::store(SETTINGS_NAME, None, &config)
-                            .expect(CANNOT_SAVE_MSG);
+                        scheduler.borrow_mut().mark_dirty();
+                    }
+
+                    true
+                },
+                SAVE_EVENT => {
+                    if scheduler.borrow().is_dirty() {
+                        config.save(&field, &word_stats.borrow(), &player_stats.borrow(), &mut scheduler.borrow_mut());
                     }
 
                     true
@@ -158,155 +774,90 @@ pub fn app() -> Result<(), confy::ConfyError> {
         }
     });
 
-    wind.draw(move |_w| {
+    wind.draw(move |w| {
+
+        board_view_draw.sync(&field_draw);
 
-        draw_scores(field_draw.get_scores());
+        arm_pulse(w, &field_draw, &is_pulse_armed);
+
+        draw_scores(field_draw.get_scores(), sidebar_x(), board_view_draw.flash());
 
         draw_longest_word(field_draw.get_longest_word());
 
-        draw_finish_button();
+        draw_finish_button(field_draw.is_finished());
+
+        match sidebar_view_draw.get() {
+            SidebarView::Leaders => draw_leaders_table(
+                &leaders::select(
+                    &table_of_leaders_draw.borrow(),
+                    leaders_sort_draw.get(),
+                    &leaders_filter_draw.borrow()
+                ),
+                leaders_sort_draw.get(),
+                sidebar_scroll_draw.get()
+            ),
+            SidebarView::Stats => draw_words_stats((*word_stats_draw).borrow()),
+            SidebarView::WordLog => draw_word_log(&field_draw.get_word_log(), sidebar_scroll_draw.get()),
+            SidebarView::Player => draw_player_stats(&player_name_draw.borrow(), player_stats_draw.borrow())
+        }
 
-        draw_leaders_table((*table_of_leaders_draw).borrow());
+        draw_sound_state(&preferences::current());
 
         draw_controls();
 
-        let is_word = field_draw.is_word();
-        let is_bonus_exists = field_draw.is_bonus_exists();
-
-        draw_bg();
-
-        for i in 0..field_draw.get_height() {
-            for j in 0..field_draw.get_width() {
-                let offset = (j + i * field_draw.get_width()) * 225;
-                let color = if is_word {
-                    enums::Color::rgb_color(
-                        100,
-                        ColorGenerator::get_color_component(
-                            offset as u32,
-                            4000,
-                            68,
-                            160
-                        ),
-                        100
-                    )
-                } else {
-                    enums::Color::rgb_color(100, 100,100)
-                };
-                let bonus_color = enums::Color::rgb_color(
-                    ColorGenerator::get_color_component(
-                        offset as u32,
-                        2000,
-                        68,
-                        160
-                    ),
-                    ColorGenerator::get_color_component(
-                        offset as u32,
-                        3222,
-                        68,
-                        160
-                    ),
-                    ColorGenerator::get_color_component(
-                        offset as u32,
-                        1000,
-                        68,
-                        160
-                    )
-                );
-                let white = enums::Color::White;
-                let gray_color = enums::Color::rgb_color(63,65,82);
-                let almost_white = enums::Color::rgb_color(230,230,230);
-
-                let checked_value = field_draw.is_checked(j, i);
-                let is_on_the_bonus_line = field_draw.is_on_the_bonus_line(j,i);
-                let letter = field_draw.get(j,i).letter;
-
-                if checked_value > 0 {
-
-                    if is_word && is_bonus_exists {
-                        draw_ceil(j,i,bonus_color, white, letter);
-                    } else {
-                        draw_ceil(j,i,color,white, letter);
-                    }
+        if let Some(text) = notice_draw.borrow().as_ref() {
+            draw_notice(text);
+        }
 
-                    draw_ceil_direction(j,i,
-                                        field_draw.get_before_direction(j,i),
-                                        field_draw.get_direction(j,i)
-                    );
+        if sidebar_view_draw.get() == SidebarView::Leaders {
+            draw_leaders_toolbar(leaders_sort_draw.get(), &leaders_filter_draw.borrow());
+        }
 
-                } else if checked_value == 0 {
-                    match field_draw.get(j,i).ceil_type {
-                        CeilType::Active => {
-                            if is_word && is_bonus_exists && is_on_the_bonus_line {
-                                draw_ceil(j, i, bonus_color, almost_white, letter);
-                            } else {
-                                draw_ceil(j, i, gray_color, almost_white, letter);
-                            }
-                        },
-                        CeilType::Empty => {
-                            if is_word && is_bonus_exists && is_on_the_bonus_line {
-                                draw_empty_ceil(j, i, bonus_color);
-                            } else {
-                                draw_empty_ceil(j, i, enums::Color::rgb_color(63, 65, 82));
-                            }
-                        },
-                        CeilType::Bonus => {
-                            if is_word && field_draw.is_bonus(j,i) == true {
-                                draw_ceil(
-                                    j,
-                                    i,
-                                    bonus_color,
-                                    enums::Color::rgb_color(
-                                        230,
-                                        ColorGenerator::get_color_component(
-                                            offset as u32,
-                                            4000,
-                                            68,
-                                            160
-                                        ),
-                                        230
-                                    ),
-                                    '!'
-                                );
-                            } else {
-                                draw_empty_ceil(j,i, enums::Color::rgb_color(0,0,0));
-                            }
-                        }
-                    }
+        draw_field(&field_draw, &board_view_draw);
 
-                } else {
-                    let alpha = (checked_value.abs() as u8) / 5;
-                    let bg = enums::Color::rgb_color(63+alpha,65+alpha,82+alpha);
-                    let fg = enums::Color::rgb_color(163+alpha,165+alpha,182+alpha);
-                    draw_ceil(j, i, bg, fg, letter);
-
-                    if checked_value == -1 {
-                        field_draw.set(j, i, Ceil {
-                            checked: 0,
-                            letter: ' ',
-                            ceil_type: CeilType::Empty
-                        });
-                        field_draw.down(j,i);
-                    } else {
-                        field_draw.set(j, i, Ceil {
-                            checked: checked_value + 14,
-                            letter: field_draw.get(j, i).letter,
-                            ceil_type: CeilType::Empty
-                        });
-                    }
-                }
-            }
-        }
+        // Очки за редкость начисляются вне поля, но в предпросмотре учитываются так же, как при принятии
+        let projection = field_draw.project();
+        let rarity_bonus = if projection.is_word {
+            word_stats_draw.borrow().rarity_bonus(&projection.word, scoring_profile_draw.get())
+        } else {
+            0
+        };
+
+        draw_preview(&projection, rarity_bonus);
     });
 
     wind.end();
     wind.show();
 
-    app::add_idle3(move |_| {
-        app::sleep(0.01);
-        wind.redraw();
+    wind.set_callback(|w| {
+        w.handle_event(SAVE_EVENT);
+        w.hide();
+    });
+
+    // Сохранение проверяется на каждом кадре, а перерисовка - только пока идёт переход
+    app::add_timeout3(FRAME_INTERVAL, move |handle| {
+        // Пока открыт диалог или меню, обработчик окна ещё не завершился
+        let is_idle = app::modal().is_none() && app::grab().is_none();
+
+        if is_idle && scheduler_idle.borrow().is_due() {
+            wind.handle_event(SAVE_EVENT);
+        }
+
+        if let Some(result) = scheduler_idle.borrow().poll() {
+            report(&notice, result);
+            wind.redraw();
+        }
+
+        if is_animating() {
+            wind.redraw();
+        }
+
+        app::repeat_timeout3(FRAME_INTERVAL, handle);
     });
 
-    app.run().unwrap();
+    let result = app.run();
+
+    scheduler_exit.borrow_mut().shutdown();
 
-    Ok(())
+    result
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+use std::cell::Cell;
+use serde::{Serialize, Deserialize};
+use crate::application::{lang, settings};
+use crate::application::sound::Sound;
+use crate::application::lang::Language;
+
+// Встроенная тема или тема пользователя, имя файла которой хранится отдельно
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    User
+}
+
+impl ThemeName {
+    pub const PRESETS: [ThemeName; 3] = [ThemeName::Dark, ThemeName::Light, ThemeName::HighContrast];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ThemeName::Dark => lang::strings().theme_dark,
+            ThemeName::Light => lang::strings().theme_light,
+            ThemeName::HighContrast => lang::strings().theme_high_contrast,
+            ThemeName::User => lang::strings().theme_user
+        }
+    }
+}
+
+// Щелчками: каждая буква отдельным щелчком, слово - повторным щелчком по последней.
+// Протягиванием: буквы выделяются, пока кнопка мыши зажата, слово - когда её отпускают
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum SelectionMode {
+    #[default]
+    Click,
+    Drag
+}
+
+impl SelectionMode {
+    pub const ALL: [SelectionMode; 2] = [SelectionMode::Click, SelectionMode::Drag];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            SelectionMode::Click => lang::strings().selection_click,
+            SelectionMode::Drag => lang::strings().selection_drag
+        }
+    }
+}
+
+// Какие события игры озвучиваются. Звуки отдельных букв подчиняются щелчку, звук серии - бонусу
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundEvents {
+    pub click: bool,
+    pub undo: bool,
+    pub wrong: bool,
+    pub low_win: bool,
+    pub high_win: bool
+}
+
+impl Default for SoundEvents {
+    fn default() -> Self {
+        SoundEvents {
+            click: true,
+            undo: true,
+            wrong: true,
+            low_win: true,
+            high_win: true
+        }
+    }
+}
+
+impl SoundEvents {
+    fn flag(&mut self, sound: Sound) -> &mut bool {
+        match sound {
+            Sound::Click | Sound::Letter(_) => &mut self.click,
+            Sound::Undo => &mut self.undo,
+            Sound::Wrong => &mut self.wrong,
+            Sound::LowWin => &mut self.low_win,
+            Sound::HighWin | Sound::Streak => &mut self.high_win
+        }
+    }
+
+    pub fn is_enabled(&self, sound: Sound) -> bool {
+        match sound {
+            Sound::Click | Sound::Letter(_) => self.click,
+            Sound::Undo => self.undo,
+            Sound::Wrong => self.wrong,
+            Sound::LowWin => self.low_win,
+            Sound::HighWin | Sound::Streak => self.high_win
+        }
+    }
+
+    pub fn set_enabled(&mut self, sound: Sound, enabled: bool) {
+        *self.flag(sound) = enabled;
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    // От 0 до 1
+    pub volume: f32,
+    pub muted: bool,
+    pub sound_events: SoundEvents,
+    // Множитель скорости анимаций, 1 - обычная
+    pub animation_speed: f32,
+    // Без переливов цветов, переходы сразу в конечном состоянии
+    pub reduced_motion: bool,
+    pub theme: ThemeName,
+    pub selection_mode: SelectionMode,
+    // Применяется со следующей игры
+    pub board_width: i32,
+    pub board_height: i32,
+    pub language: Language
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            volume: 1.0,
+            muted: false,
+            sound_events: SoundEvents::default(),
+            animation_speed: 1.0,
+            reduced_motion: false,
+            theme: ThemeName::default(),
+            selection_mode: SelectionMode::default(),
+            board_width: settings::WIDTH,
+            board_height: settings::HEIGHT,
+            language: Language::default()
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Preferences> = Cell::new(Preferences::default());
+}
+
+// Настройки, действующие сейчас. Окно настроек меняет их сразу, до сохранения
+pub fn current() -> Preferences {
+    CURRENT.with(Cell::get)
+}
+
+impl Preferences {
+    // Значения из старых или испорченных сохранений приводятся к допустимым
+    pub fn normalized(&self) -> Self {
+        Preferences {
+            volume: self.volume.clamp(0.0, 1.0),
+            animation_speed: self.animation_speed.clamp(settings::MIN_ANIMATION_SPEED, settings::MAX_ANIMATION_SPEED),
+            board_width: self.board_width.clamp(settings::MIN_BOARD_SIZE, settings::MAX_BOARD_SIZE),
+            board_height: self.board_height.clamp(settings::MIN_BOARD_SIZE, settings::MAX_BOARD_SIZE),
+            ..*self
+        }
+    }
+
+    pub fn apply(&self) {
+        let preferences = self.normalized();
+
+        CURRENT.with(|current| current.set(preferences));
+        lang::set_language(preferences.language);
+    }
+
+    pub fn board_size(&self) -> (i32, i32) {
+        (self.board_width, self.board_height)
+    }
+}
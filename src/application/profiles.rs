@@ -0,0 +1,62 @@
+use serde::{Serialize, Deserialize};
+use crate::application::settings;
+use crate::field::*;
+use crate::stats::*;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub best_score: i32,
+    pub best_word: String,
+    pub total_score: i64,
+    pub total_words: u32
+}
+
+impl PlayerStats {
+    pub fn record_game(&mut self, field: &Field) {
+        let scores = field.get_scores();
+        let longest_word = field.get_longest_word();
+
+        self.games_played += 1;
+        self.total_score += scores as i64;
+        self.total_words += field.get_word_log().len() as u32;
+
+        if self.best_score < scores {
+            self.best_score = scores;
+        }
+
+        if self.best_word.chars().count() < longest_word.chars().count() {
+            self.best_word = longest_word;
+        }
+    }
+
+    pub fn average_score(&self) -> i64 {
+        match self.games_played {
+            0 => 0,
+            games => self.total_score / games as i64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub field: Field,
+    pub word_stats: WordStats,
+    pub stats: PlayerStats
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        let field = Field::new(settings::WIDTH, settings::HEIGHT);
+
+        field.generate();
+
+        Profile {
+            name: name.to_string(),
+            field,
+            word_stats: WordStats::default(),
+            stats: PlayerStats::default()
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Serialize, Deserialize};
+use crate::application::storage::StorageError;
+use crate::application::save;
+use crate::field::*;
+
+// Запись игры: поле в момент начала и все действия игрока по порядку.
+// Принятые слова хранятся ради очков, которые начисляются вне поля, например за редкость
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub width: i32,
+    pub height: i32,
+    pub letters: Vec<String>,
+    pub tiles: Vec<String>,
+    pub steps: Vec<ReplayStep>,
+    pub words: Vec<WordRecord>
+}
+
+impl Replay {
+    // Без начального поля игру не воспроизвести
+    pub fn from_field(field: &Field) -> Option<Self> {
+        let initial = field.get_initial_board();
+
+        if initial.is_empty() {
+            return None;
+        }
+
+        let (letters, tiles) = save::encode_board(&initial);
+
+        Some(Replay {
+            width: field.get_width(),
+            height: field.get_height(),
+            letters,
+            tiles,
+            steps: field.get_moves(),
+            words: field.get_word_log()
+        })
+    }
+
+    // Поле, с которого начиналась игра
+    pub fn to_field(&self) -> Result<Field, StorageError> {
+        let board = save::decode_board(self.width, self.height, &self.letters, &self.tiles)?;
+        let field = Field::new(self.width, self.height);
+
+        field.set_board(board.clone());
+        field.set_initial_board(board);
+
+        Ok(field)
+    }
+
+    pub fn apply(&self, field: &Field, step: &ReplayStep) -> CheckOutcome {
+        let outcome = match step.action {
+            Move::Select(x, y) if x < 0 || y < 0 || x >= field.get_width() || y >= field.get_height() => {
+                CheckOutcome::Ignored
+            },
+            Move::Select(x, y) => field.try_check(x, y),
+            Move::Deselect => {
+                field.deselect();
+                CheckOutcome::Ignored
+            }
+        };
+
+        if let CheckOutcome::Accepted(_) = outcome {
+            let idx = field.get_word_log().len() - 1;
+
+            if let Some(record) = self.words.get(idx) {
+                field.reward_last_word(record.rarity_score);
+            }
+        }
+
+        outcome
+    }
+}
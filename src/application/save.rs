@@ -0,0 +1,555 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize, Deserializer};
+use crate::application::{lang, settings};
+use crate::application::preferences::Preferences;
+use crate::application::storage::StorageError;
+use crate::field::*;
+use crate::leaders::*;
+use crate::profiles::*;
+use crate::stats::*;
+
+// Формат сохранения не зависит от внутреннего устройства игры: в нём нет состояния выделения,
+// анимации и прочих служебных полей. Любое изменение формата увеличивает SAVE_VERSION
+// и добавляет шаг в цепочку миграций в parse
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub profiles: Vec<SavedProfile>,
+    pub active_profile: usize,
+    pub position_x: i32,
+    pub position_y: i32,
+    pub leaders: Vec<LeaderRecord>,
+    pub corpus_path: Option<String>,
+    pub scoring_profile: ScoringProfile,
+    pub last_player_name: String,
+    #[serde(default)]
+    pub preferences: Preferences,
+    #[serde(default)]
+    pub sound_pack: Option<String>,
+    #[serde(default)]
+    pub user_theme: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedProfile {
+    pub name: String,
+    pub board: SavedBoard,
+    pub played_words: HashMap<String, u32>,
+    pub stats: PlayerStats
+}
+
+// Буквы и типы клеток хранятся построчно: '.' - клетка с буквой, '#' - бонусная, ' ' - пустая.
+// Начальное поле неизвестно у игр, начатых до появления экспорта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBoard {
+    pub width: i32,
+    pub height: i32,
+    pub letters: Vec<String>,
+    pub tiles: Vec<String>,
+    #[serde(default)]
+    pub initial_letters: Vec<String>,
+    #[serde(default)]
+    pub initial_tiles: Vec<String>,
+    pub scores: i32,
+    pub longest_word: String,
+    pub word_log: Vec<WordRecord>,
+    #[serde(default)]
+    pub moves: Vec<ReplayStep>,
+    pub seed: u64,
+    pub started_at: u64,
+    pub mode: GameMode,
+    pub finished: bool
+}
+
+pub fn encode_board(board: &[Vec<Ceil>]) -> (Vec<String>, Vec<String>) {
+    let letters = board.iter()
+        .map(|row| row.iter().map(|ceil| ceil.letter).collect())
+        .collect();
+    let tiles = board.iter()
+        .map(|row| row.iter()
+            .map(|ceil| match ceil.ceil_type {
+                CeilType::Active => '.',
+                CeilType::Bonus => '#',
+                CeilType::Empty => ' '
+            })
+            .collect())
+        .collect();
+
+    (letters, tiles)
+}
+
+pub fn decode_board(width: i32, height: i32, letters: &[String], tiles: &[String]) -> Result<Vec<Vec<Ceil>>, StorageError> {
+    if width <= 0 || height <= 0 || letters.len() != height as usize || tiles.len() != height as usize {
+        return Err(StorageError::Invalid(String::from("board size does not match its rows")));
+    }
+
+    let mut board = vec![];
+
+    for (y, (letters_row, tiles_row)) in letters.iter().zip(tiles.iter()).enumerate() {
+        let letters: Vec<char> = letters_row.chars().collect();
+        let tiles: Vec<char> = tiles_row.chars().collect();
+
+        if letters.len() != width as usize || tiles.len() != width as usize {
+            return Err(StorageError::Invalid(format!("row {} has wrong length", y)));
+        }
+
+        let mut row = vec![];
+
+        for x in 0..width as usize {
+            let ceil_type = match tiles[x] {
+                '.' => CeilType::Active,
+                '#' => CeilType::Bonus,
+                ' ' => CeilType::Empty,
+                tile => return Err(StorageError::Invalid(format!("unknown tile '{}'", tile)))
+            };
+
+            row.push(Ceil {
+                letter: letters[x],
+                checked: 0,
+                ceil_type
+            });
+        }
+
+        board.push(row);
+    }
+
+    Ok(board)
+}
+
+impl SavedBoard {
+    pub fn from_field(field: &Field) -> Self {
+        let (letters, tiles) = encode_board(&field.get_board());
+        let (initial_letters, initial_tiles) = encode_board(&field.get_initial_board());
+
+        SavedBoard {
+            width: field.get_width(),
+            height: field.get_height(),
+            letters,
+            tiles,
+            initial_letters,
+            initial_tiles,
+            scores: field.get_scores(),
+            longest_word: field.get_longest_word(),
+            word_log: field.get_word_log(),
+            moves: field.get_moves(),
+            seed: field.get_seed(),
+            started_at: field.get_started_at(),
+            mode: field.get_mode(),
+            finished: field.is_finished()
+        }
+    }
+
+    pub fn to_field(&self) -> Result<Field, StorageError> {
+        let board = decode_board(self.width, self.height, &self.letters, &self.tiles)?;
+        let initial = if self.initial_letters.is_empty() {
+            vec![]
+        } else {
+            decode_board(self.width, self.height, &self.initial_letters, &self.initial_tiles)?
+        };
+
+        let field = Field::new(self.width, self.height);
+
+        field.set_board(board);
+        field.set_initial_board(initial);
+        field.set_scores(self.scores);
+        field.set_longest_word(self.longest_word.clone());
+        field.set_word_log(self.word_log.clone());
+        field.set_moves(self.moves.clone());
+        field.set_seed(self.seed);
+        field.set_started_at(self.started_at);
+        field.set_mode(self.mode);
+        field.set_finished(self.finished);
+
+        Ok(field)
+    }
+}
+
+impl SavedProfile {
+    pub fn from_profile(profile: &Profile) -> Self {
+        SavedProfile {
+            name: profile.name.clone(),
+            board: SavedBoard::from_field(&profile.field),
+            played_words: profile.word_stats.get_played_words().clone(),
+            stats: profile.stats.clone()
+        }
+    }
+
+    pub fn to_profile(&self) -> Result<Profile, StorageError> {
+        Ok(Profile {
+            name: self.name.clone(),
+            field: self.board.to_field()?,
+            word_stats: WordStats::from_played_words(self.played_words.clone()),
+            stats: self.stats.clone()
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    version: u32
+}
+
+// Разбор сохранения любой известной версии с приведением к текущей. Сохранение более новой
+// версии не читается: незнакомые поля потерялись бы при следующей записи
+pub fn parse(content: &str) -> Result<SaveGame, StorageError> {
+    let probe: VersionProbe = ron::from_str(content).map_err(StorageError::Parse)?;
+
+    match probe.version {
+        version if version > settings::SAVE_VERSION => {
+            Err(StorageError::Invalid(String::from(lang::strings().newer_save)))
+        },
+        0 | 1 => {
+            let config: legacy::ConfigV1 = ron::from_str(content).map_err(StorageError::Parse)?;
+            Ok(legacy::migrate_v1(legacy::migrate_v0(config)))
+        },
+        _ => ron::from_str(content).map_err(StorageError::Parse)
+    }
+}
+
+// Отдельный файл с одной игрой, который можно передать другому игроку
+#[derive(Serialize, Deserialize)]
+struct ExportedGame {
+    version: u32,
+    board: SavedBoard
+}
+
+pub fn export_game(field: &Field, path: &Path) -> Result<(), StorageError> {
+    let game = ExportedGame {
+        version: settings::SAVE_VERSION,
+        board: SavedBoard::from_field(field)
+    };
+
+    let content = ron::ser::to_string_pretty(&game, ron::ser::PrettyConfig::default())
+        .map_err(StorageError::Serialize)?;
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+pub fn import_game(path: &Path) -> Result<Field, StorageError> {
+    parse_game(&fs::read_to_string(path)?)
+}
+
+// Файлы игр появились сразу с версией, поэтому файл без неё или из более новой версии не читается
+fn parse_game(content: &str) -> Result<Field, StorageError> {
+    let probe: VersionProbe = ron::from_str(content).map_err(StorageError::Parse)?;
+
+    match probe.version {
+        0 => return Err(StorageError::Invalid(String::from(lang::strings().unversioned_game_file))),
+        version if version > settings::SAVE_VERSION => {
+            return Err(StorageError::Invalid(String::from(lang::strings().newer_game_file)));
+        },
+        _ => {}
+    }
+
+    let game: ExportedGame = ron::from_str(content).map_err(StorageError::Parse)?;
+
+    game.board.to_field()
+}
+
+// Сохранения версий 0 и 1 были прямой сериализацией структур игры
+mod legacy {
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct CeilV1 {
+        pub letter: char,
+        pub checked: i32,
+        pub ceil_type: CeilType
+    }
+
+    #[derive(Deserialize)]
+    pub struct FieldV1 {
+        pub scores: i32,
+        pub most_lengthy_word: String,
+        #[serde(default)]
+        pub word_log: Vec<WordRecord>,
+        #[serde(default)]
+        pub seed: u64,
+        #[serde(default)]
+        pub started_at: u64,
+        #[serde(default)]
+        pub mode: GameMode,
+        #[serde(default)]
+        pub finished: bool,
+        pub height: i32,
+        pub width: i32,
+        pub data: Vec<Vec<CeilV1>>
+    }
+
+    #[derive(Default, Deserialize)]
+    pub struct WordStatsV1 {
+        pub played: HashMap<String, u32>
+    }
+
+    #[derive(Deserialize)]
+    pub struct ProfileV1 {
+        pub name: String,
+        pub field: FieldV1,
+        #[serde(default)]
+        pub word_stats: WordStatsV1,
+        #[serde(default)]
+        pub stats: PlayerStats
+    }
+
+    // Версия 0 - сохранения без профилей игроков, с полем и статистикой слов в корне
+    #[derive(Deserialize)]
+    pub struct ConfigV1 {
+        #[serde(default, deserialize_with = "deserialize_some")]
+        pub field: Option<FieldV1>,
+        #[serde(default, deserialize_with = "deserialize_some")]
+        pub word_stats: Option<WordStatsV1>,
+        #[serde(default)]
+        pub profiles: Vec<ProfileV1>,
+        #[serde(default)]
+        pub active_profile: usize,
+        pub position_x: i32,
+        pub position_y: i32,
+        #[serde(deserialize_with = "deserialize_leaders")]
+        pub table_of_leaders: Vec<LeaderRecord>,
+        #[serde(default)]
+        pub corpus_path: Option<String>,
+        #[serde(default)]
+        pub scoring_profile: ScoringProfile,
+        #[serde(default)]
+        pub last_player_name: String
+    }
+
+    fn deserialize_some<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+        T::deserialize(deserializer).map(Some)
+    }
+
+    pub fn migrate_v0(mut config: ConfigV1) -> ConfigV1 {
+        if let Some(field) = config.field.take() {
+            if config.profiles.is_empty() {
+                config.profiles.push(ProfileV1 {
+                    name: String::from(settings::DEFAULT_PLAYER_NAME),
+                    field,
+                    word_stats: config.word_stats.take().unwrap_or_default(),
+                    stats: PlayerStats::default()
+                });
+            }
+        }
+
+        config
+    }
+
+    // Клетки, которые исчезали в момент сохранения, считаются уже исчезнувшими
+    fn migrate_field(field: FieldV1) -> SavedBoard {
+        let mut columns: Vec<Vec<(char, char)>> = vec![vec![]; field.width as usize];
+
+        for row in field.data.iter() {
+            for (x, ceil) in row.iter().enumerate().take(field.width as usize) {
+                let tile = match ceil.ceil_type {
+                    CeilType::Active => '.',
+                    CeilType::Bonus => '#',
+                    CeilType::Empty => ' '
+                };
+
+                if ceil.checked >= 0 {
+                    columns[x].push((ceil.letter, tile));
+                }
+            }
+        }
+
+        for column in columns.iter_mut() {
+            while column.len() < field.height as usize {
+                column.insert(0, (' ', ' '));
+            }
+        }
+
+        let mut letters = vec![];
+        let mut tiles = vec![];
+
+        for y in 0..field.height as usize {
+            letters.push(columns.iter().map(|column| column[y].0).collect());
+            tiles.push(columns.iter().map(|column| column[y].1).collect());
+        }
+
+        SavedBoard {
+            width: field.width,
+            height: field.height,
+            letters,
+            tiles,
+            initial_letters: vec![],
+            initial_tiles: vec![],
+            scores: field.scores,
+            longest_word: field.most_lengthy_word,
+            word_log: field.word_log,
+            moves: vec![],
+            seed: field.seed,
+            started_at: field.started_at,
+            mode: field.mode,
+            finished: field.finished
+        }
+    }
+
+    pub fn migrate_v1(config: ConfigV1) -> SaveGame {
+        SaveGame {
+            version: 2,
+            profiles: config.profiles.into_iter()
+                .map(|profile| SavedProfile {
+                    name: profile.name,
+                    board: migrate_field(profile.field),
+                    played_words: profile.word_stats.played,
+                    stats: profile.stats
+                })
+                .collect(),
+            active_profile: config.active_profile,
+            position_x: config.position_x,
+            position_y: config.position_y,
+            leaders: config.table_of_leaders,
+            corpus_path: config.corpus_path,
+            scoring_profile: config.scoring_profile,
+            last_player_name: config.last_player_name,
+            preferences: Preferences::default(),
+            sound_pack: None,
+            user_theme: None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE_V0: &str = include_str!("../../tests/fixtures/save_v0.ron");
+    const SAVE_V1: &str = include_str!("../../tests/fixtures/save_v1.ron");
+
+    fn letter(board: &SavedBoard, x: usize, y: usize) -> char {
+        board.letters[y].chars().nth(x).unwrap()
+    }
+
+    fn tile(board: &SavedBoard, x: usize, y: usize) -> char {
+        board.tiles[y].chars().nth(x).unwrap()
+    }
+
+    #[test]
+    fn migrates_v0() {
+        let save = parse(SAVE_V0).unwrap();
+
+        assert_eq!(save.version, 2);
+        assert_eq!((save.position_x, save.position_y), (120, 80));
+        assert_eq!(save.active_profile, 0);
+        assert_eq!(save.profiles.len(), 1);
+
+        let profile = &save.profiles[0];
+        assert_eq!(profile.name, settings::DEFAULT_PLAYER_NAME);
+        assert!(profile.played_words.is_empty());
+        assert_eq!(profile.board.scores, 42);
+        assert_eq!(profile.board.longest_word, "КОТЁНОК");
+        assert!(!profile.board.finished);
+
+        // Исчезавшее слово в 7-й строке убрано, буквы над ним опустились
+        assert_eq!((letter(&profile.board, 2, 0), tile(&profile.board, 2, 0)), (' ', ' '));
+        assert_eq!(letter(&profile.board, 2, 7), 'Г');
+        assert_eq!(letter(&profile.board, 5, 7), 'Ю');
+        assert_eq!(tile(&profile.board, 5, 14), '#');
+
+        // Пары (очки, слово) превращены в записи, нулевой результат отброшен
+        assert_eq!(save.leaders.len(), 2);
+        assert_eq!(save.leaders[0].score, 300);
+        assert_eq!(save.leaders[0].longest_word, "ПРИВЕТ");
+        assert_eq!(save.leaders[1].score, 120);
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let save = parse(SAVE_V1).unwrap();
+
+        assert_eq!(save.version, 2);
+        assert_eq!(save.active_profile, 1);
+        assert_eq!(save.profiles.len(), 2);
+        assert_eq!(save.scoring_profile, ScoringProfile::Rarity);
+        assert_eq!(save.corpus_path.as_deref(), Some("/home/user/corpus.txt"));
+        assert_eq!(save.last_player_name, "Игрок");
+        assert_eq!(save.leaders.len(), 1);
+        assert_eq!(save.leaders[0].scoring_profile, ScoringProfile::Rarity);
+
+        let profile = &save.profiles[0];
+        assert_eq!(profile.played_words.get("кот"), Some(&3));
+        assert_eq!(profile.stats.games_played, 2);
+        assert_eq!(profile.board.word_log.len(), 1);
+        assert_eq!(profile.board.seed, 12345);
+        assert_eq!(profile.board.started_at, 1699999000);
+        assert_eq!((letter(&profile.board, 0, 0), tile(&profile.board, 0, 0)), (' ', ' '));
+        assert_eq!(letter(&profile.board, 9, 3), 'У');
+        assert_eq!(letter(&profile.board, 0, 1), 'З');
+
+        assert_eq!(save.profiles[1].name, "Маша");
+        assert!(save.profiles[1].board.finished);
+    }
+
+    #[test]
+    fn rejects_newer_save() {
+        let content = format!("(version: {}, profiles: [])", settings::SAVE_VERSION + 1);
+
+        assert!(matches!(parse(&content), Err(StorageError::Invalid(_))));
+    }
+
+    #[test]
+    fn current_version_round_trip() {
+        let save = parse(SAVE_V1).unwrap();
+        let content = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()).unwrap();
+        let reloaded = parse(&content).unwrap();
+
+        assert_eq!(
+            content,
+            ron::ser::to_string_pretty(&reloaded, ron::ser::PrettyConfig::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn board_round_trip() {
+        let save = parse(SAVE_V1).unwrap();
+        let board = &save.profiles[0].board;
+        let field = board.to_field().unwrap();
+
+        // Выделение не сохраняется, а поле восстанавливается как было
+        assert_eq!(field.is_checked(9, 3), 0);
+        assert_eq!(field.get_scores(), 64);
+        assert_eq!(SavedBoard::from_field(&field).letters, board.letters);
+        assert_eq!(SavedBoard::from_field(&field).tiles, board.tiles);
+    }
+
+    #[test]
+    fn imports_current_game() {
+        let board = parse(SAVE_V1).unwrap().profiles.remove(0).board;
+        let game = ExportedGame { version: settings::SAVE_VERSION, board };
+        let content = ron::ser::to_string_pretty(&game, ron::ser::PrettyConfig::default()).unwrap();
+        let field = parse_game(&content).unwrap();
+
+        assert_eq!(field.get_scores(), 64);
+        assert_eq!(SavedBoard::from_field(&field).letters, game.board.letters);
+    }
+
+    #[test]
+    fn rejects_unversioned_game() {
+        let content = "(board: ())";
+
+        assert!(matches!(parse_game(content), Err(StorageError::Invalid(_))));
+    }
+
+    #[test]
+    fn rejects_newer_game() {
+        let content = format!("(version: {}, board: ())", settings::SAVE_VERSION + 1);
+
+        assert!(matches!(parse_game(&content), Err(StorageError::Invalid(_))));
+    }
+
+    #[test]
+    fn rejects_malformed_board() {
+        let mut board = parse(SAVE_V1).unwrap().profiles.remove(0).board;
+        board.tiles[0] = board.tiles[0].replacen('.', "?", 1);
+
+        assert!(board.to_field().is_err());
+
+        board.letters.pop();
+
+        assert!(board.to_field().is_err());
+    }
+}
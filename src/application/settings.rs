@@ -1,13 +1,72 @@
 pub const WIDTH: i32 = 15;
 pub const HEIGHT: i32 = 15;
-pub const TITLE: &str = "Альфабит";
+pub const DEFAULT_PLAYER_NAME: &str = "Игрок";
 pub const OFFSET_Y: i32 = 40;
-pub const SETTINGS_NAME: &str = "settings";
+pub const APP_NAME: &str = "alphabit";
+pub const SAVE_NAME: &str = "save";
+// Имя, под которым сохранялись старые версии игры
+pub const LEGACY_SETTINGS_NAME: &str = "settings";
+pub const PORTABLE_FILE_NAME: &str = "alphabit.ron";
+pub const PORTABLE_MARKER: &str = "portable";
 pub const CELL_SIZE: i32 = 40;
 pub const SIDEBAR_WIDTH: i32 = 300;
-
-pub const CANNOT_SAVE_MSG: &str = "Не удалось сохранить настройки";
+pub const SIDEBAR_ROWS: i32 = 10;
+pub const SOUND_TOGGLE_WIDTH: i32 = 40;
+// Полоса предпросмотра выделенного слова под полем
+pub const PREVIEW_HEIGHT: i32 = 40;
+pub const REPLAY_MIN_DELAY_MS: u64 = 150;
+pub const REPLAY_MAX_DELAY_MS: u64 = 1500;
+pub const STATS_ROWS: i32 = 5;
+pub const LEADERS_LIMIT: usize = 10;
+pub const SAVE_DEBOUNCE_MS: u64 = 500;
+pub const SAVE_MAX_DELAY_MS: u64 = 5000;
+pub const SAVE_VERSION: u32 = 2;
+pub const MIN_BOARD_SIZE: i32 = 8;
+pub const MAX_BOARD_SIZE: i32 = 20;
+pub const MIN_ANIMATION_SPEED: f32 = 0.25;
+pub const MAX_ANIMATION_SPEED: f32 = 4.0;
+// Сколько гаснут клетки принятого слова и сколько потом падают верхние клетки, при обычной скорости
+pub const REMOVAL_MS: u32 = 250;
+pub const FALL_MS: u32 = 200;
+// Эффекты принятого слова: всплывающие очки, искры от бонусной линии и вспышка счёта
+pub const POPUP_MS: u32 = 700;
+pub const POPUP_RISE: f32 = 30.0;
+pub const PARTICLE_MS: u32 = 600;
+pub const PARTICLES_PER_CEIL: usize = 5;
+// Сколько пикселей искра пролетает за всю жизнь и насколько успевает упасть
+pub const PARTICLE_DISTANCE: f32 = 40.0;
+pub const PARTICLE_FALL: f32 = 30.0;
+pub const PARTICLE_SIZE: i32 = 5;
+pub const FLASH_MS: u32 = 300;
+// Как часто окно перерисовывается, пока что-то анимируется, секунды
+pub const FRAME_INTERVAL: f64 = 0.01;
+// Как часто окно перерисовывается ради переливания цветов темы, секунды
+pub const PULSE_INTERVAL: f64 = 0.1;
+// За сколько правильное слово проявляется на поле
+pub const WORD_HIGHLIGHT_MS: u32 = 250;
+// Сколько звуков может звучать одновременно
+pub const SOUND_CHANNELS: usize = 4;
+pub const SOUND_PACKS_DIR: &str = "sounds";
+pub const SOUND_PACK_MANIFEST: &str = "pack.ron";
+pub const THEMES_DIR: &str = "themes";
+// Каждое такое число слов подряд без ошибок и отмен звучит звук серии
+pub const STREAK_WORDS: u32 = 3;
+// Каждая следующая буква выделения звучит чуть выше предыдущей
+pub const SELECTION_PITCH_STEP: f32 = 0.05;
+// Каждая буква слова сверх минимальной длины повышает звук принятого слова
+pub const WORD_PITCH_STEP: f32 = 0.04;
+pub const MAX_PITCH: f32 = 1.6;
+// С какой длины слова к звуку добавляются голоса выше: на квинту и на октаву
+pub const WORD_LAYERS: [(usize, f32); 2] = [(6, 1.5), (8, 2.0)];
+pub const LAYER_VOLUME: f32 = 0.5;
+// Мажорное трезвучие от до второй октавы при очистке бонусной линии
+pub const CHORD_ROOT_HZ: f32 = 523.25;
+pub const CHORD_RATIOS: [f32; 3] = [1.0, 1.25, 1.5];
+pub const CHORD_MS: u64 = 600;
+pub const CHORD_VOLUME: f32 = 0.12;
 
 pub const SCORES_FOR_BONUS_AND_CHECKED: i32 = 100;
 pub const SCORES_FOR_CHECKED: i32 = 50;
-pub const SCORES_FOR_BONUS_LINE: i32 = 5;
\ No newline at end of file
+pub const SCORES_FOR_BONUS_LINE: i32 = 5;
+pub const SCORES_FOR_RARE_WORD: i32 = 100;
+pub const MIN_WORD_LENGTH: usize = 3;
@@ -0,0 +1,63 @@
+use crate::application::dictionary;
+use crate::field::*;
+
+// Все слова, которые можно составить на поле по правилам игры:
+// из соседних (в том числе по диагонали) букв, не используя букву дважды
+pub fn solve(field: &Field) -> Vec<String> {
+    let dictionary = dictionary::get();
+    let mut words = vec![];
+    let mut visited = vec![vec![false; field.get_width() as usize]; field.get_height() as usize];
+
+    for y in 0..field.get_height() {
+        for x in 0..field.get_width() {
+            search(field, dictionary, x, y, &mut String::new(), &mut visited, &mut words);
+        }
+    }
+
+    words.sort_by(|a, b| {
+        b.chars().count().cmp(&a.chars().count()).then_with(|| a.cmp(b))
+    });
+    words.dedup();
+
+    words
+}
+
+fn search(
+    field: &Field,
+    dictionary: &dictionary::Dictionary,
+    x: i32,
+    y: i32,
+    word: &mut String,
+    visited: &mut [Vec<bool>],
+    words: &mut Vec<String>
+) {
+    let ceil = field.get(x, y);
+
+    if !matches!(ceil.ceil_type, CeilType::Active) || visited[y as usize][x as usize] {
+        return;
+    }
+
+    word.push(ceil.letter);
+
+    if dictionary.is_prefix(word) {
+        if dictionary.is_word(word) {
+            words.push(word.to_lowercase());
+        }
+
+        visited[y as usize][x as usize] = true;
+
+        for offset_y in -1..=1 {
+            for offset_x in -1..=1 {
+                let (nx, ny) = (x + offset_x, y + offset_y);
+
+                if nx >= 0 && ny >= 0 && nx < field.get_width() && ny < field.get_height() {
+                    search(field, dictionary, nx, ny, word, visited, words);
+                }
+            }
+        }
+
+        visited[y as usize][x as usize] = false;
+    }
+
+    word.pop();
+}
@@ -1,6 +1,14 @@
-use std::io::{Cursor};
-use std::thread;
-use rodio::{Decoder, OutputStream, source::Source};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::PathBuf;
+use std::time::Duration;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, buffer::SamplesBuffer, source::{SineWave, Source}};
+use serde::Deserialize;
+use crate::application::{lang, preferences, settings, storage};
+use crate::application::storage::StorageError;
+use crate::field::*;
 
 const CLICK_SOUND: &[u8] = include_bytes!("../../assets/sounds/click.wav");
 const HIGH_WIN_SOUND: &[u8] = include_bytes!("../../assets/sounds/high_win.wav");
@@ -8,52 +16,353 @@ const LOW_WIN_SOUND: &[u8] = include_bytes!("../../assets/sounds/low_win.wav");
 const UNDO_SOUND: &[u8] = include_bytes!("../../assets/sounds/undo.wav");
 const WRONG_SOUND: &[u8] = include_bytes!("../../assets/sounds/wrongway.wav");
 
-pub fn play_click() {
-    thread::spawn(|| {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let slice = Cursor::new(CLICK_SOUND.as_ref());
-        let source = Decoder::new(slice).unwrap();
-        let _sound_result = stream_handle.play_raw(source.convert_samples());
-        std::thread::sleep(std::time::Duration::from_millis(3000));
-    });
+// События игры, которые могут звучать. У первых пяти есть встроенные звуки,
+// остальные звучат, только если их задаёт набор звуков
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sound {
+    Click,
+    HighWin,
+    LowWin,
+    Undo,
+    Wrong,
+    // Выбор конкретной буквы, без своего звука звучит как Click
+    Letter(char),
+    // Несколько слов подряд без ошибок и отмен
+    Streak
+}
+
+impl Sound {
+    // События со встроенными звуками, которые можно включать и выключать по отдельности
+    pub const ALL: [Sound; 5] = [Sound::Click, Sound::HighWin, Sound::LowWin, Sound::Undo, Sound::Wrong];
+
+    // Имя события в описании набора звуков
+    pub fn key(&self) -> String {
+        match self {
+            Sound::Click => String::from("click"),
+            Sound::HighWin => String::from("high_win"),
+            Sound::LowWin => String::from("low_win"),
+            Sound::Undo => String::from("undo"),
+            Sound::Wrong => String::from("wrong"),
+            Sound::Letter(letter) => format!("letter_{}", letter.to_lowercase()),
+            Sound::Streak => String::from("streak")
+        }
+    }
+
+    // Событие, чей звук звучит, если у этого своего нет ни в наборе, ни среди встроенных
+    fn fallback(&self) -> Option<Sound> {
+        match self {
+            Sound::Letter(_) => Some(Sound::Click),
+            _ => None
+        }
+    }
+
+    fn data(&self) -> Option<&'static [u8]> {
+        match self {
+            Sound::Click => Some(CLICK_SOUND),
+            Sound::HighWin => Some(HIGH_WIN_SOUND),
+            Sound::LowWin => Some(LOW_WIN_SOUND),
+            Sound::Undo => Some(UNDO_SOUND),
+            Sound::Wrong => Some(WRONG_SOUND),
+            Sound::Letter(_) | Sound::Streak => None
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Sound::Click | Sound::Letter(_) => lang::strings().sound_click,
+            Sound::HighWin | Sound::Streak => lang::strings().sound_high_win,
+            Sound::LowWin => lang::strings().sound_low_win,
+            Sound::Undo => lang::strings().sound_undo,
+            Sound::Wrong => lang::strings().sound_wrong
+        }
+    }
+}
+
+// Звук, раскодированный заранее, чтобы не разбирать файл при каждом щелчке
+struct Clip {
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<i16>
+}
+
+impl Clip {
+    fn decode<R: Read + Seek + Send + Sync + 'static>(reader: R) -> Option<Self> {
+        let decoder = Decoder::new(reader).ok()?;
+
+        Some(Clip {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            samples: decoder.collect()
+        })
+    }
+
+    fn source(&self) -> SamplesBuffer<i16> {
+        SamplesBuffer::new(self.channels, self.sample_rate, self.samples.clone())
+    }
+}
+
+type Voice = Box<dyn Source<Item = f32> + Send>;
+
+// Как звучит событие: высота относительно записи, голоса выше основного и аккорд поверх
+#[derive(Clone, Debug)]
+pub struct Voicing {
+    pub pitch: f32,
+    pub layers: Vec<f32>,
+    pub chord: bool
+}
+
+impl Default for Voicing {
+    fn default() -> Self {
+        Voicing {
+            pitch: 1.0,
+            layers: vec![],
+            chord: false
+        }
+    }
+}
+
+impl Voicing {
+    // Чем длиннее выделение, тем выше щелчок
+    pub fn for_selection(length: usize) -> Self {
+        Voicing {
+            pitch: (1.0 + length.saturating_sub(1) as f32 * settings::SELECTION_PITCH_STEP).min(settings::MAX_PITCH),
+            ..Voicing::default()
+        }
+    }
+
+    // Длинное слово звучит выше и в несколько голосов, бонусная линия добавляет аккорд
+    pub fn for_word(length: usize, is_bonus: bool) -> Self {
+        let extra = length.saturating_sub(settings::MIN_WORD_LENGTH) as f32;
+
+        Voicing {
+            pitch: (1.0 + extra * settings::WORD_PITCH_STEP).min(settings::MAX_PITCH),
+            layers: settings::WORD_LAYERS.iter()
+                .filter(|(min_length, _)| length >= *min_length)
+                .map(|(_, ratio)| *ratio)
+                .collect(),
+            chord: is_bonus
+        }
+    }
+
+    fn voice(&self, clip: &Clip) -> Voice {
+        let mut voice: Voice = Box::new(clip.source().convert_samples::<f32>().speed(self.pitch));
+
+        for ratio in &self.layers {
+            let layer = clip.source()
+                .convert_samples::<f32>()
+                .speed(self.pitch * ratio)
+                .amplify(settings::LAYER_VOLUME);
+            voice = Box::new(voice.mix(layer));
+        }
+
+        if self.chord {
+            voice = Box::new(voice.mix(chord()));
+        }
+
+        voice
+    }
+}
+
+fn chord() -> Voice {
+    let tone = |ratio: f32| {
+        let mut tone = SineWave::new(settings::CHORD_ROOT_HZ * ratio)
+            .take_duration(Duration::from_millis(settings::CHORD_MS));
+        tone.set_filter_fadeout();
+        tone.amplify(settings::CHORD_VOLUME)
+    };
+    let [root, third, fifth] = settings::CHORD_RATIOS;
+
+    Box::new(tone(root).mix(tone(third)).mix(tone(fifth)))
+}
+
+// Описание набора звуков: каталог с файлом pack.ron вида
+// (sounds: {"click": "click.ogg", "letter_а": "a.wav", "streak": "streak.flac"})
+// Пути к файлам указываются относительно каталога набора
+#[derive(Deserialize)]
+struct PackManifest {
+    sounds: HashMap<String, String>
+}
+
+fn packs_dir() -> Result<PathBuf, StorageError> {
+    let path = storage::get_path()?;
+
+    path.parent()
+        .map(|dir| dir.join(settings::SOUND_PACKS_DIR))
+        .ok_or_else(|| StorageError::Invalid(String::from("save path has no parent directory")))
+}
+
+// Наборы звуков, которые лежат в каталоге наборов рядом с сохранением
+pub fn packs() -> Vec<String> {
+    let entries = match packs_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) {
+        Ok(entries) => entries,
+        Err(_) => return vec![]
+    };
+
+    let mut packs: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(settings::SOUND_PACK_MANIFEST).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    packs.sort();
+
+    packs
+}
+
+// Файлы, которых нет или которые не удалось прочитать, пропускаются: на их месте звучат встроенные звуки
+fn load_pack(name: &str) -> Result<HashMap<String, Clip>, StorageError> {
+    let dir = packs_dir()?.join(name);
+    let content = fs::read_to_string(dir.join(settings::SOUND_PACK_MANIFEST))?;
+    let manifest: PackManifest = ron::from_str(&content).map_err(StorageError::Parse)?;
+
+    Ok(manifest.sounds.into_iter()
+        .filter_map(|(event, file)| {
+            let reader = BufReader::new(fs::File::open(dir.join(file)).ok()?);
+            Some((event, Clip::decode(reader)?))
+        })
+        .collect())
+}
+
+// Одно устройство вывода на всю игру и несколько каналов, чтобы звуки могли накладываться
+struct AudioService {
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    sinks: Vec<Sink>,
+    next: usize,
+    defaults: HashMap<String, Clip>,
+    pack: HashMap<String, Clip>
+}
+
+impl AudioService {
+    fn open() -> Option<Self> {
+        let (stream, handle) = OutputStream::try_default().ok()?;
+        let sinks = (0..settings::SOUND_CHANNELS)
+            .map(|_| Sink::try_new(&handle))
+            .collect::<Result<Vec<Sink>, _>>()
+            .ok()?;
+
+        let defaults = Sound::ALL.iter()
+            .filter_map(|sound| Some((sound.key(), Clip::decode(Cursor::new(sound.data()?))?)))
+            .collect();
+
+        Some(AudioService {
+            _stream: stream,
+            handle,
+            sinks,
+            next: 0,
+            defaults,
+            pack: HashMap::new()
+        })
+    }
+
+    // Свой звук из набора, потом встроенный, потом звук запасного события
+    fn clip(&self, sound: Sound) -> Option<&Clip> {
+        let key = sound.key();
+
+        self.pack.get(&key)
+            .or_else(|| self.defaults.get(&key))
+            .or_else(|| self.clip(sound.fallback()?))
+    }
+
+    // Свободный канал, а если все заняты - самый давно занятый, который начинается заново
+    fn sink(&mut self) -> Option<&Sink> {
+        let idx = match self.sinks.iter().position(Sink::empty) {
+            Some(idx) => idx,
+            None => {
+                let idx = self.next;
+                self.next = (self.next + 1) % self.sinks.len();
+                self.sinks[idx] = Sink::try_new(&self.handle).ok()?;
+                idx
+            }
+        };
+
+        self.sinks.get(idx)
+    }
+
+    fn play(&mut self, sound: Sound, voicing: &Voicing, volume: f32) {
+        let source = match self.clip(sound) {
+            Some(clip) => voicing.voice(clip),
+            None => return
+        };
+
+        if let Some(sink) = self.sink() {
+            sink.set_volume(volume);
+            sink.append(source);
+        }
+    }
 }
 
-pub fn play_high_win() {
-    thread::spawn(|| {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let slice = Cursor::new(HIGH_WIN_SOUND.as_ref());
-        let source = Decoder::new(slice).unwrap();
-        let _sound_result = stream_handle.play_raw(source.convert_samples());
-        std::thread::sleep(std::time::Duration::from_millis(3000));
+thread_local! {
+    // Без устройства вывода игра просто остаётся без звука
+    static SERVICE: Option<RefCell<AudioService>> = AudioService::open().map(RefCell::new);
+}
+
+// Открывает устройство заранее, чтобы первый звук не запаздывал
+pub fn init() {
+    SERVICE.with(|_| {});
+}
+
+// None - только встроенные звуки
+pub fn set_pack(name: Option<&str>) -> Result<(), StorageError> {
+    let pack = match name {
+        Some(name) => load_pack(name)?,
+        None => HashMap::new()
+    };
+
+    SERVICE.with(|service| {
+        if let Some(service) = service {
+            service.borrow_mut().pack = pack;
+        }
     });
+
+    Ok(())
+}
+
+pub fn play(sound: Sound) {
+    play_voiced(sound, &Voicing::default());
 }
 
-pub fn play_low_win() {
-    thread::spawn(|| {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let slice = Cursor::new(LOW_WIN_SOUND.as_ref());
-        let source = Decoder::new(slice).unwrap();
-        let _sound_result = stream_handle.play_raw(source.convert_samples());
-        std::thread::sleep(std::time::Duration::from_millis(3000));
+pub fn play_voiced(sound: Sound, voicing: &Voicing) {
+    let preferences = preferences::current();
+
+    if preferences.muted || preferences.volume <= 0.0 || !preferences.sound_events.is_enabled(sound) {
+        return;
+    }
+
+    SERVICE.with(|service| {
+        if let Some(service) = service {
+            service.borrow_mut().play(sound, voicing, preferences.volume);
+        }
     });
 }
 
 pub fn play_undo() {
-    thread::spawn(move || {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let slice = Cursor::new(UNDO_SOUND.as_ref());
-        let source = Decoder::new(slice).unwrap();
-        let _sound_result = stream_handle.play_raw(source.convert_samples());
-        std::thread::sleep(std::time::Duration::from_millis(3000));
-    });
+    play(Sound::Undo);
 }
 
 pub fn play_wrong() {
-    thread::spawn(move || {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let slice = Cursor::new(WRONG_SOUND.as_ref());
-        let source = Decoder::new(slice).unwrap();
-        let _sound_result = stream_handle.play_raw(source.convert_samples());
-        std::thread::sleep(std::time::Duration::from_millis(3000));
-    });
-}
\ No newline at end of file
+    play(Sound::Wrong);
+}
+
+// Выбранная буква звучит своим звуком, если он есть в наборе.
+// Бонусные очки начисляются только при наличии бонусной линии
+pub fn play_outcome(outcome: &CheckOutcome, field: &Field) {
+    match outcome {
+        CheckOutcome::Selected => {
+            let word = field.get_word();
+            let voicing = Voicing::for_selection(word.chars().count());
+
+            match word.chars().last() {
+                Some(letter) => play_voiced(Sound::Letter(letter), &voicing),
+                None => play_voiced(Sound::Click, &voicing)
+            }
+        },
+        CheckOutcome::Accepted(record) => {
+            let is_bonus = record.bonus_score > 0;
+            let sound = if is_bonus { Sound::HighWin } else { Sound::LowWin };
+
+            play_voiced(sound, &Voicing::for_word(record.word.chars().count(), is_bonus));
+        },
+        CheckOutcome::Wrong => play_wrong(),
+        CheckOutcome::Ignored => {}
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fs;
+use serde::{Serialize, Deserialize};
+use crate::application::{lang, settings};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScoringProfile {
+    #[default]
+    Classic,
+    Rarity
+}
+
+impl ScoringProfile {
+    pub const ALL: [ScoringProfile; 2] = [ScoringProfile::Classic, ScoringProfile::Rarity];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ScoringProfile::Classic => lang::strings().scoring_classic,
+            ScoringProfile::Rarity => lang::strings().scoring_rarity
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct WordStats {
+    played: HashMap<String, u32>,
+    corpus: Option<HashMap<String, usize>>
+}
+
+impl WordStats {
+    pub fn from_played_words(played: HashMap<String, u32>) -> Self {
+        WordStats {
+            played,
+            corpus: None
+        }
+    }
+
+    pub fn get_played_words(&self) -> &HashMap<String, u32> {
+        &self.played
+    }
+
+    pub fn record(&mut self, word: &str) {
+        *self.played.entry(word.to_lowercase()).or_insert(0) += 1;
+    }
+
+    // Частотный словарь общий для всех игроков, поэтому переносится только история слов
+    pub fn assign_played(&mut self, other: &WordStats) {
+        self.played = other.played.clone();
+    }
+
+    pub fn get_played(&self, word: &str) -> u32 {
+        *self.played.get(&word.to_lowercase()).unwrap_or(&0)
+    }
+
+    pub fn most_played(&self, count: usize) -> Vec<(String, u32)> {
+        let mut words = self.sorted();
+        words.reverse();
+        words.truncate(count);
+        words
+    }
+
+    pub fn least_played(&self, count: usize) -> Vec<(String, u32)> {
+        let mut words = self.sorted();
+        words.truncate(count);
+        words
+    }
+
+    fn sorted(&self) -> Vec<(String, u32)> {
+        let mut words: Vec<(String, u32)> = self.played
+            .iter()
+            .map(|(word, count)| (word.clone(), *count))
+            .collect();
+
+        words.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+        words
+    }
+
+    // Частотный словарь - по слову в строке, первым словом в строке идёт само слово,
+    // строки отсортированы от самых частых слов к самым редким
+    pub fn load_corpus(&mut self, path: &str) -> std::io::Result<()> {
+        let content = fs::read_to_string(path)?;
+        let mut corpus = HashMap::new();
+
+        for line in content.lines() {
+            if let Some(word) = line.split_whitespace().next() {
+                let rank = corpus.len();
+                corpus.entry(word.to_lowercase()).or_insert(rank);
+            }
+        }
+
+        self.corpus = Some(corpus);
+
+        Ok(())
+    }
+
+    // Редкость слова от 0 до 1: чем реже слово встречается в частотном словаре
+    // и чем реже его составляли раньше, тем она выше
+    pub fn rarity(&self, word: &str) -> f64 {
+        let word = word.to_lowercase();
+
+        let corpus_rarity = match &self.corpus {
+            Some(corpus) if !corpus.is_empty() => match corpus.get(&word) {
+                Some(rank) => *rank as f64 / corpus.len() as f64,
+                None => 1.0
+            },
+            _ => 1.0
+        };
+
+        corpus_rarity / (1 + self.get_played(&word)) as f64
+    }
+
+    pub fn rarity_bonus(&self, word: &str, profile: ScoringProfile) -> i32 {
+        match profile {
+            ScoringProfile::Classic => 0,
+            ScoringProfile::Rarity => {
+                (settings::SCORES_FOR_RARE_WORD as f64 * self.rarity(word)).round() as i32
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::application::{lang, settings};
+
+#[derive(Debug)]
+pub enum StorageError {
+    Path(confy::ConfyError),
+    Io(io::Error),
+    Serialize(ron::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String)
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Path(err) => write!(f, "{}", err),
+            StorageError::Io(err) => write!(f, "{}", err),
+            StorageError::Serialize(err) => write!(f, "{}", err),
+            StorageError::Parse(err) => write!(f, "{}", err),
+            StorageError::Invalid(err) => write!(f, "{}", err)
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+pub struct Loaded<T> {
+    pub value: T,
+    pub warning: Option<String>
+}
+
+// Где хранится сохранение: в каталоге настроек пользователя, рядом с исполняемым файлом
+// или по явно указанному пути
+#[derive(Clone, Debug)]
+pub enum Location {
+    Default,
+    Portable,
+    Custom(PathBuf)
+}
+
+static LOCATION: OnceLock<Location> = OnceLock::new();
+
+// Выбирается один раз при запуске, до первого обращения к сохранению
+pub fn set_location(location: Location) {
+    let _ = LOCATION.set(location);
+}
+
+fn executable_dir() -> Result<PathBuf, StorageError> {
+    let executable = std::env::current_exe()?;
+
+    executable.parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| StorageError::Invalid(String::from("executable has no parent directory")))
+}
+
+// Портативный режим включается и файлом-меткой рядом с исполняемым файлом
+fn location() -> Location {
+    match LOCATION.get() {
+        Some(location) => location.clone(),
+        None => match executable_dir() {
+            Ok(dir) if dir.join(settings::PORTABLE_MARKER).exists() => Location::Portable,
+            _ => Location::Default
+        }
+    }
+}
+
+pub fn get_path() -> Result<PathBuf, StorageError> {
+    match location() {
+        Location::Default => confy::get_configuration_file_path(settings::APP_NAME, settings::SAVE_NAME)
+            .map_err(StorageError::Path),
+        Location::Portable => Ok(executable_dir()?.join(settings::PORTABLE_FILE_NAME)),
+        Location::Custom(path) => Ok(path)
+    }
+}
+
+// Старые версии хранили игру под общим именем "settings", которое могут занимать и другие программы,
+// поэтому переносится только файл, который удаётся прочитать как сохранение игры
+fn migrate_legacy<T>(path: &Path, parse: fn(&str) -> Result<T, StorageError>) -> Option<T> {
+    if !matches!(location(), Location::Default) {
+        return None;
+    }
+
+    let legacy_path = confy::get_configuration_file_path(settings::LEGACY_SETTINGS_NAME, None).ok()?;
+    let value = read(&legacy_path, parse).ok()?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).ok()?;
+    }
+    fs::copy(&legacy_path, path).ok()?;
+
+    Some(value)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn read<T>(path: &Path, parse: fn(&str) -> Result<T, StorageError>) -> Result<T, StorageError> {
+    let content = fs::read_to_string(path)?;
+
+    parse(&content)
+}
+
+// Если сохранение повреждено, пробуем резервную копию, а если нет и её - начинаем с чистого листа.
+// Повреждённый файл не удаляется, а откладывается в сторону
+pub fn load<T: Default>(parse: fn(&str) -> Result<T, StorageError>) -> Loaded<T> {
+    let path = match get_path() {
+        Ok(path) => path,
+        Err(err) => return Loaded {
+            value: T::default(),
+            warning: Some(format!("{}: {}", lang::strings().cannot_load, err))
+        }
+    };
+
+    let err = match read(&path, parse) {
+        Ok(value) => return Loaded { value, warning: None },
+        Err(StorageError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            return Loaded {
+                value: migrate_legacy(&path, parse).unwrap_or_default(),
+                warning: None
+            };
+        },
+        Err(err) => err
+    };
+
+    let _ = fs::rename(&path, with_suffix(&path, ".broken"));
+
+    match read(&with_suffix(&path, ".bak"), parse) {
+        Ok(value) => Loaded {
+            value,
+            warning: Some(format!("{}: {}\n{}", lang::strings().cannot_load, err, lang::strings().backup_restored))
+        },
+        Err(_) => Loaded {
+            value: T::default(),
+            warning: Some(format!("{}: {}\n{}", lang::strings().cannot_load, err, lang::strings().defaults_restored))
+        }
+    }
+}
+
+// Запись во временный файл с последующим переименованием, чтобы сбой посреди записи
+// не оставил после себя обрезанное сохранение. Предыдущее сохранение становится резервной копией
+pub fn store<T: Serialize>(value: &T) -> Result<(), StorageError> {
+    let path = get_path()?;
+
+    let content = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(StorageError::Serialize)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp_path = with_suffix(&path, ".tmp");
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        fs::copy(&path, with_suffix(&path, ".bak"))?;
+    }
+
+    fs::rename(&temp_path, &path)?;
+
+    Ok(())
+}
+
+// Сохранение не удаляется насовсем, а становится резервной копией
+pub fn reset() -> Result<(), StorageError> {
+    let path = get_path()?;
+
+    if path.exists() {
+        fs::rename(&path, with_suffix(&path, ".bak"))?;
+    }
+
+    Ok(())
+}
+
+// Отложенное сохранение: изменения копятся, пока пользователь активен, и записываются
+// в фоновом потоке после короткой паузы, но не реже, чем раз в SAVE_MAX_DELAY_MS
+pub struct SaveScheduler<T> {
+    first_change: Option<Instant>,
+    last_change: Instant,
+    sender: Option<Sender<T>>,
+    results: Receiver<Result<(), StorageError>>,
+    worker: Option<JoinHandle<()>>
+}
+
+impl<T: Serialize + Send + 'static> Default for SaveScheduler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Serialize + Send + 'static> SaveScheduler<T> {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<T>();
+        let (results_sender, results) = mpsc::channel();
+
+        let worker = thread::spawn(move || {
+            for value in receiver {
+                if results_sender.send(store(&value)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        SaveScheduler {
+            first_change: None,
+            last_change: Instant::now(),
+            sender: Some(sender),
+            results,
+            worker: Some(worker)
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.last_change = Instant::now();
+
+        if self.first_change.is_none() {
+            self.first_change = Some(self.last_change);
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.first_change.is_some()
+    }
+
+    pub fn is_due(&self) -> bool {
+        match self.first_change {
+            Some(first_change) => {
+                self.last_change.elapsed() >= Duration::from_millis(settings::SAVE_DEBOUNCE_MS) ||
+                    first_change.elapsed() >= Duration::from_millis(settings::SAVE_MAX_DELAY_MS)
+            },
+            None => false
+        }
+    }
+
+    pub fn flush(&mut self, value: T) {
+        self.first_change = None;
+
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(value);
+        }
+    }
+
+    // Результат последней завершённой записи, если таковая была с прошлой проверки
+    pub fn poll(&self) -> Option<Result<(), StorageError>> {
+        self.results.try_iter().last()
+    }
+
+    // Дожидается записи всего, что было отправлено, перед выходом из игры
+    pub fn shutdown(&mut self) {
+        self.sender.take();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
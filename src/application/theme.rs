@@ -0,0 +1,229 @@
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::application::{settings, storage};
+use crate::application::preferences::ThemeName;
+use crate::application::storage::StorageError;
+
+pub type Rgb = (u8, u8, u8);
+
+// Цвет, который переливается между двумя значениями, каждый канал со своим периодом
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pulse {
+    pub from: Rgb,
+    pub to: Rgb
+}
+
+impl Pulse {
+    pub const fn steady(color: Rgb) -> Self {
+        Pulse { from: color, to: color }
+    }
+
+    pub fn is_steady(&self) -> bool {
+        self.from == self.to
+    }
+}
+
+// Все цвета игры. В файле темы можно указать только часть цветов, остальные берутся из тёмной темы
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub background: Pulse,
+    pub tile: Rgb,
+    pub letter: Rgb,
+    pub selection: Rgb,
+    pub selection_letter: Rgb,
+    pub valid_selection: Pulse,
+    pub path: Rgb,
+    // Рамка и галочка правильного слова, штриховка бонусной линии - чтобы их было видно не только по цвету
+    pub outline: Rgb,
+    pub hatch: Rgb,
+    pub bonus: Pulse,
+    pub bonus_tile: Rgb,
+    pub bonus_mark: Pulse,
+    pub fading_tile: Rgb,
+    pub fading_letter: Rgb,
+    pub header_score: Pulse,
+    pub header_word: Pulse,
+    pub header_button: Pulse,
+    pub header_replay: Rgb,
+    pub header_text: Rgb,
+    pub sidebar_header: Rgb,
+    pub sidebar_row: Rgb,
+    pub sidebar_row_alt: Rgb,
+    pub sidebar_text: Rgb,
+    pub sidebar_details: Rgb,
+    pub sidebar_toolbar: Rgb,
+    pub controls_row: Rgb,
+    pub controls_row_alt: Rgb,
+    pub warning: Rgb
+}
+
+pub const DARK: Theme = Theme {
+    background: Pulse { from: (60, 80, 100), to: (80, 100, 120) },
+    tile: (63, 65, 82),
+    letter: (230, 230, 230),
+    selection: (100, 100, 100),
+    selection_letter: (255, 255, 255),
+    valid_selection: Pulse { from: (100, 68, 100), to: (100, 160, 100) },
+    path: (255, 255, 255),
+    outline: (255, 255, 255),
+    hatch: (30, 30, 30),
+    bonus: Pulse { from: (68, 68, 68), to: (160, 160, 160) },
+    bonus_tile: (0, 0, 0),
+    bonus_mark: Pulse { from: (230, 68, 230), to: (230, 160, 230) },
+    fading_tile: (113, 115, 132),
+    fading_letter: (213, 215, 232),
+    header_score: Pulse { from: (120, 50, 40), to: (180, 50, 40) },
+    header_word: Pulse { from: (0, 140, 190), to: (0, 140, 230) },
+    header_button: Pulse { from: (50, 180, 160), to: (50, 220, 160) },
+    header_replay: (90, 60, 140),
+    header_text: (255, 255, 255),
+    sidebar_header: (50, 90, 130),
+    sidebar_row: (0x3f, 0x41, 0x52),
+    sidebar_row_alt: (0x3f - 10, 0x41 - 10, 0x52 - 10),
+    sidebar_text: (255, 255, 255),
+    sidebar_details: (170, 170, 190),
+    sidebar_toolbar: (50, 80, 130),
+    controls_row: (50, 60, 70),
+    controls_row_alt: (40, 50, 60),
+    warning: (150, 40, 40)
+};
+
+pub const LIGHT: Theme = Theme {
+    background: Pulse { from: (190, 200, 215), to: (210, 220, 235) },
+    tile: (236, 236, 242),
+    letter: (40, 40, 50),
+    selection: (120, 120, 135),
+    selection_letter: (255, 255, 255),
+    valid_selection: Pulse { from: (90, 140, 90), to: (90, 190, 90) },
+    path: (255, 255, 255),
+    outline: (20, 20, 30),
+    hatch: (60, 60, 70),
+    bonus: Pulse { from: (150, 150, 150), to: (230, 230, 230) },
+    bonus_tile: (90, 90, 100),
+    bonus_mark: Pulse { from: (120, 30, 120), to: (120, 90, 120) },
+    fading_tile: (160, 160, 175),
+    fading_letter: (250, 250, 250),
+    header_score: Pulse { from: (200, 90, 80), to: (230, 90, 80) },
+    header_word: Pulse { from: (40, 150, 200), to: (40, 150, 230) },
+    header_button: Pulse { from: (60, 170, 150), to: (60, 200, 150) },
+    header_replay: (130, 100, 180),
+    header_text: (255, 255, 255),
+    sidebar_header: (80, 120, 170),
+    sidebar_row: (228, 230, 238),
+    sidebar_row_alt: (218, 220, 228),
+    sidebar_text: (30, 30, 40),
+    sidebar_details: (90, 90, 110),
+    sidebar_toolbar: (80, 110, 160),
+    controls_row: (200, 205, 215),
+    controls_row_alt: (190, 195, 205),
+    warning: (190, 40, 40)
+};
+
+// Без переливов и с наибольшей разницей яркости между соседними цветами
+pub const HIGH_CONTRAST: Theme = Theme {
+    background: Pulse::steady((0, 0, 0)),
+    tile: (25, 25, 25),
+    letter: (255, 255, 255),
+    selection: (255, 255, 0),
+    selection_letter: (0, 0, 0),
+    valid_selection: Pulse::steady((0, 255, 0)),
+    path: (0, 0, 0),
+    outline: (255, 255, 255),
+    hatch: (0, 0, 0),
+    bonus: Pulse::steady((0, 255, 255)),
+    bonus_tile: (255, 255, 255),
+    bonus_mark: Pulse::steady((255, 0, 255)),
+    fading_tile: (128, 128, 128),
+    fading_letter: (255, 255, 255),
+    header_score: Pulse::steady((0, 0, 0)),
+    header_word: Pulse::steady((0, 0, 90)),
+    header_button: Pulse::steady((0, 90, 0)),
+    header_replay: (90, 0, 90),
+    header_text: (255, 255, 255),
+    sidebar_header: (0, 0, 90),
+    sidebar_row: (0, 0, 0),
+    sidebar_row_alt: (30, 30, 30),
+    sidebar_text: (255, 255, 255),
+    sidebar_details: (255, 255, 0),
+    sidebar_toolbar: (0, 0, 90),
+    controls_row: (0, 0, 0),
+    controls_row_alt: (30, 30, 30),
+    warning: (200, 0, 0)
+};
+
+impl Theme {
+    // Есть ли в теме хоть один переливающийся цвет. Если нет, окну незачем перерисовываться само по себе
+    pub fn is_animated(&self) -> bool {
+        [
+            self.background,
+            self.valid_selection,
+            self.bonus,
+            self.bonus_mark,
+            self.header_score,
+            self.header_word,
+            self.header_button
+        ].iter().any(|pulse| !pulse.is_steady())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        DARK
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Theme> = const { Cell::new(DARK) };
+}
+
+pub fn current() -> Theme {
+    CURRENT.with(Cell::get)
+}
+
+pub fn set(theme: Theme) {
+    CURRENT.with(|current| current.set(theme));
+}
+
+fn themes_dir() -> Result<PathBuf, StorageError> {
+    let path = storage::get_path()?;
+
+    path.parent()
+        .map(|dir| dir.join(settings::THEMES_DIR))
+        .ok_or_else(|| StorageError::Invalid(String::from("save path has no parent directory")))
+}
+
+// Темы пользователя - файлы *.ron в каталоге тем рядом с сохранением
+pub fn user_themes() -> Vec<String> {
+    let entries = match themes_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) {
+        Ok(entries) => entries,
+        Err(_) => return vec![]
+    };
+
+    let mut themes: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    themes.sort();
+
+    themes
+}
+
+pub fn load(name: ThemeName, user_theme: Option<&str>) -> Result<Theme, StorageError> {
+    match (name, user_theme) {
+        (ThemeName::Dark, _) => Ok(DARK),
+        (ThemeName::Light, _) => Ok(LIGHT),
+        (ThemeName::HighContrast, _) => Ok(HIGH_CONTRAST),
+        (ThemeName::User, Some(user_theme)) => {
+            let path = themes_dir()?.join(format!("{}.ron", user_theme));
+            let content = fs::read_to_string(path)?;
+
+            ron::from_str(&content).map_err(StorageError::Parse)
+        },
+        (ThemeName::User, None) => Err(StorageError::Invalid(String::from("theme file is not chosen")))
+    }
+}
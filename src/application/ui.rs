@@ -1,8 +1,145 @@
-use std::cell::{Ref};
-use fltk::{*, draw::*};
-use crate::application::{animation};
+use std::cell::{Cell, Ref, RefCell};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use fltk::{*, draw::*, prelude::*};
+use crate::application::{animation, preferences, sound, theme};
+use crate::application::lang::{self, Language};
+use crate::application::preferences::{Preferences, SelectionMode, ThemeName};
+use crate::application::animation::{Easing, Tween};
+use crate::application::effects::Effects;
+use crate::application::theme::{Pulse, Rgb};
+use crate::application::replay::Replay;
 use crate::settings::*;
 use crate::field::*;
+use crate::stats::*;
+use crate::leaders::*;
+use crate::profiles::*;
+
+thread_local! {
+    // Размер поля в клетках, под который размечено главное окно
+    static BOARD_SIZE: Cell<(i32, i32)> = const { Cell::new((WIDTH, HEIGHT)) };
+}
+
+pub fn set_board_size(width: i32, height: i32) {
+    BOARD_SIZE.with(|size| size.set((width, height)));
+}
+
+// Окно не бывает меньше поля 15x15, чтобы помещались заголовок и боковая панель
+pub fn board_area(width: i32, height: i32) -> (i32, i32) {
+    (width.max(WIDTH) * CELL_SIZE, height.max(HEIGHT) * CELL_SIZE)
+}
+
+pub fn sidebar_x() -> i32 {
+    let (width, height) = BOARD_SIZE.with(Cell::get);
+    board_area(width, height).0
+}
+
+pub fn window_size() -> (i32, i32) {
+    let (width, height) = BOARD_SIZE.with(Cell::get);
+    let (area_width, area_height) = board_area(width, height);
+    (area_width + SIDEBAR_WIDTH, area_height + OFFSET_Y + PREVIEW_HEIGHT)
+}
+
+// Окно часто перерисовывается само, только пока идёт переход
+pub fn is_animating() -> bool {
+    animation::is_running()
+}
+
+// Переливание цветов темы медленное, для него хватает редкой перерисовки. Заметно оно
+// только на выделенном слове и бонусных линиях при нём, без них окно не перерисовывается
+pub fn is_pulsing(field: &Field) -> bool {
+    animation::is_pulsing() && theme::current().is_animated() && field.is_word()
+}
+
+// Уход принятого слова: сначала гаснут убранные клетки, потом падают верхние
+struct ClearingView {
+    clearing: Clearing,
+    fade: Tween,
+    fall: Tween
+}
+
+// То, что поле показывает сверх своего состояния. У каждого окна с полем - своё
+#[derive(Default)]
+pub struct BoardView {
+    // Проявление правильного слова, начинается с первой перерисовки, на которой слово стало правильным
+    highlight: Cell<Option<Tween>>,
+    clearing: RefCell<Option<ClearingView>>,
+    effects: RefCell<Effects>
+}
+
+impl BoardView {
+    // Забирает у поля последнее принятое слово, чтобы показать его уход и эффекты.
+    // Вызывается в начале каждой перерисовки окна с полем
+    pub fn sync(&self, field: &Field) {
+        if let Some(clearing) = field.take_clearing() {
+            self.effects.borrow_mut().push(&clearing);
+            *self.clearing.borrow_mut() = Some(ClearingView {
+                clearing,
+                fade: Tween::start(REMOVAL_MS, Easing::Linear),
+                fall: Tween::after(REMOVAL_MS, FALL_MS, Easing::EaseIn)
+            });
+        }
+
+        if !self.is_clearing() {
+            self.clearing.take();
+        }
+    }
+
+    // Пока слово уходит, поле не принимает новых действий
+    pub fn is_clearing(&self) -> bool {
+        self.clearing.borrow().as_ref().is_some_and(|view| !view.fall.is_finished())
+    }
+
+    // Поле заменили целиком, старый переход к нему уже не относится
+    pub fn reset(&self) {
+        self.highlight.take();
+        self.clearing.take();
+        self.effects.borrow_mut().clear();
+    }
+
+    pub fn flash(&self) -> f32 {
+        self.effects.borrow().flash()
+    }
+
+    fn highlight(&self, is_word: bool) -> f32 {
+        if is_word {
+            let started = self.highlight.get().unwrap_or_else(|| Tween::start(WORD_HIGHLIGHT_MS, Easing::EaseOut));
+            self.highlight.set(Some(started));
+            started.value()
+        } else {
+            self.highlight.set(None);
+            0.0
+        }
+    }
+}
+
+pub fn rgb(color: Rgb) -> enums::Color {
+    enums::Color::rgb_color(color.0, color.1, color.2)
+}
+
+pub fn mix(from: Rgb, to: Rgb, t: f32) -> enums::Color {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    enums::Color::rgb_color(channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+}
+
+// Каждый канал переливается со своим периодом, смещение разводит соседние клетки по фазе
+fn pulse(pulse: &Pulse, offset: u32, durations: [u32; 3]) -> enums::Color {
+    let channel = |from: u8, to: u8, duration: u32| {
+        if from == to {
+            from
+        } else {
+            let t = animation::pulse(offset, duration);
+            (from as f32 + (to as f32 - from as f32) * t).round() as u8
+        }
+    };
+
+    enums::Color::rgb_color(
+        channel(pulse.from.0, pulse.to.0, durations[0]),
+        channel(pulse.from.1, pulse.to.1, durations[1]),
+        channel(pulse.from.2, pulse.to.2, durations[2])
+    )
+}
 
 pub fn draw_direction(j:i32, i:i32, direction:Direction)
 {
@@ -40,38 +177,75 @@ pub fn draw_direction(j:i32, i:i32, direction:Direction)
     }
 }
 
+// Клетка в любом месте окна, а не только в своей ячейке, например во время падения
+fn draw_tile(left: i32, top: i32, bg: enums::Color, letter: Option<(enums::Color, char)>) {
+    draw_rect_fill(left + 1, top + 1, 38, 38, bg);
+
+    if let Some((fg, letter)) = letter {
+        set_draw_color(fg);
+        draw_text( &format!("{}", letter), left + 15, top + 25);
+    }
+}
+
 pub fn draw_empty_ceil(x: i32, y: i32, bg: enums::Color) {
-    draw_rect_fill(x * CELL_SIZE + 1, y * CELL_SIZE + 1 + OFFSET_Y, 38, 38, bg);
+    draw_tile(x * CELL_SIZE, y * CELL_SIZE + OFFSET_Y, bg, None);
 }
 
 pub fn draw_ceil(x: i32, y: i32, bg: enums::Color, fg: enums::Color, letter: char) {
-    draw_empty_ceil(x,y,bg);
-    set_draw_color(fg);
-    draw_text( &format!("{}", letter), x * CELL_SIZE + 15, y * CELL_SIZE + 25 + OFFSET_Y);
+    draw_tile(x * CELL_SIZE, y * CELL_SIZE + OFFSET_Y, bg, Some((fg, letter)));
+}
+
+// Рамка вокруг клеток правильного слова
+pub fn draw_word_outline(x: i32, y: i32, color: enums::Color) {
+    set_draw_color(color);
+    set_line_style(LineStyle::Solid, 3);
+    draw_rect(x * CELL_SIZE + 3, y * CELL_SIZE + 3 + OFFSET_Y, 34, 34);
+}
+
+// Галочка в правом верхнем углу, в стороне от буквы
+pub fn draw_checkmark(x: i32, y: i32, color: enums::Color) {
+    let left = x * CELL_SIZE;
+    let top = y * CELL_SIZE + OFFSET_Y;
+
+    set_draw_color(color);
+    set_line_style(LineStyle::Solid, 2);
+    draw_line(left + 26, top + 8, left + 29, top + 12);
+    draw_line(left + 29, top + 12, left + 35, top + 5);
+}
+
+// Штриховка в углах клетки бонусной линии. Буква в середине клетки остаётся читаемой
+pub fn draw_bonus_hatch(x: i32, y: i32, color: enums::Color) {
+    let left = x * CELL_SIZE;
+    let top = y * CELL_SIZE + OFFSET_Y;
+
+    set_draw_color(color);
+    set_line_style(LineStyle::Solid, 1);
+    for step in [6, 12, 18] {
+        draw_line(left + 1, top + step, left + step, top + 1);
+        draw_line(left + CELL_SIZE - step, top + CELL_SIZE - 1, left + CELL_SIZE - 1, top + CELL_SIZE - step);
+    }
 }
 
 pub fn draw_ceil_direction(x: i32, y: i32, before_direction: Direction, after_direction: Direction) {
-    set_draw_color(enums::Color::White);
+    set_draw_color(rgb(theme::current().path));
     draw_direction(x,y,before_direction);
     draw_direction(x,y,after_direction);
 }
 
-pub fn draw_scores(scores: i32) {
-    let red = animation::ColorGenerator::get_color_component(
-        500, 2000,120, 180
-    );
+// Вспышка от 0 до 1 осветляет заголовок сразу после принятого слова
+pub fn draw_scores(scores: i32, width: i32, flash: f32) {
+    let theme = theme::current();
+    let background = pulse(&theme.header_score, 500, [2000; 3]).to_rgb();
     set_font(enums::Font::Courier, 16);
-    draw_rect_fill( 0,0,40 * WIDTH, OFFSET_Y,enums::Color::rgb_color(red,50, 40));
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("ОЧКИ: {:0>5}", scores), 15, 25);
+    draw_rect_fill( 0,0,width, OFFSET_Y,mix(background, theme.header_text, flash * 0.6));
+    set_draw_color(rgb(theme.header_text));
+    draw_text( &format!("{}: {:0>5}", lang::strings().scores, scores), 15, 25);
 }
 
 pub fn draw_longest_word(word: String) {
-    let blue = animation::ColorGenerator::get_color_component(
-        1000, 2000,190, 230
-    );
-    draw_rect_fill(150,0,300, OFFSET_Y, enums::Color::rgb_color(0,140,blue));
-    set_draw_color(enums::Color::rgb_color(255,255,255));
+    let theme = theme::current();
+    draw_rect_fill(150,0,300, OFFSET_Y, pulse(&theme.header_word, 1000, [2000; 3]));
+    set_draw_color(rgb(theme.header_text));
     if word.len() > 0 {
         draw_text( &format!("{:^25}", word.to_uppercase()), 175, 25);
     } else {
@@ -79,84 +253,937 @@ pub fn draw_longest_word(word: String) {
     }
 }
 
-pub fn draw_finish_button() {
-    let green = animation::ColorGenerator::get_color_component(
-        1000, 2000,180, 220
-    );
-    draw_rect_fill(450,0,150, OFFSET_Y, enums::Color::rgb_color(50,green,160));
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "ФИНИШ"), 500, 25);
-}
+// Полоса под полем: выделенные буквы, слово ли это, и что за него дадут вместе с бонусными линиями
+pub fn draw_preview(projection: &WordProjection, rarity_bonus: i32) {
+    let strings = lang::strings();
+    let theme = theme::current();
+    let (width, height) = window_size();
+    let top = height - PREVIEW_HEIGHT;
 
-pub fn draw_leaders_table(leaders: Ref<Vec<(i32,String)>>) {
+    draw_rect_fill(0, top, width, PREVIEW_HEIGHT, rgb(theme.sidebar_toolbar));
+    set_draw_color(rgb(theme.header_text));
 
-    let x = WIDTH * CELL_SIZE;
-    let color = enums::Color::rgb_color(50, 90, 130);
-    draw_rect_fill(x, 0, SIDEBAR_WIDTH, CELL_SIZE, color);
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "ТАБЛИЦА ЛИДЕРОВ"), WIDTH * CELL_SIZE + 80, 25);
+    if projection.word.is_empty() {
+        draw_text(strings.preview_empty, 15, top + 25);
+        return;
+    }
 
-    for (y, record) in leaders.iter().enumerate() {
+    let word = projection.word.to_uppercase();
+    let label = match (projection.is_word, projection.is_bonus) {
+        (true, true) => format!("{} ✓ {}, {}", word, strings.selection_word, strings.selection_bonus),
+        (true, false) => format!("{} ✓ {}", word, strings.selection_word),
+        (false, _) => format!("{} · {}", word, strings.selection_not_word)
+    };
+    draw_text(&label, 15, top + 25);
 
-        let offset = (y + 1) as u8;
-        let color = if offset % 2 == 1 {
-            enums::Color::rgb_color( 0x3f,0x41,0x52)
-        }  else {
-            enums::Color::rgb_color( 0x3f - 10,0x41 - 10,0x52 - 10)
-        };
-        draw_rect_fill(x, CELL_SIZE * (offset as i32), SIDEBAR_WIDTH, CELL_SIZE, color);
-        set_draw_color(enums::Color::rgb_color(255,255,255));
-        if record.0 > 0 {
-            draw_text(&format!("{:<5} {}", record.0, record.1), WIDTH * CELL_SIZE + 10, CELL_SIZE * (offset as i32) + 25);
+    if projection.is_word {
+        let rarity = if rarity_bonus > 0 {
+            format!(", {} +{}", strings.preview_rarity, rarity_bonus)
         } else {
-            draw_text(&format!("{}", "-"), WIDTH * CELL_SIZE + 10, CELL_SIZE * (offset as i32) + 25);
+            String::new()
+        };
+        let scores = format!(
+            "{}: +{} ({} +{}{})  {}: {}",
+            strings.preview_scores,
+            projection.base_score + projection.bonus_score + rarity_bonus,
+            strings.selection_bonus,
+            projection.bonus_score,
+            rarity,
+            strings.preview_cleared,
+            projection.cleared
+        );
+        let (scores_width, _) = measure(&scores, false);
+        draw_text(&scores, width - scores_width - 15, top + 25);
+    }
+}
+
+pub fn draw_finish_button(is_finished: bool) {
+    let theme = theme::current();
+    draw_rect_fill(450,0,150, OFFSET_Y, pulse(&theme.header_button, 1000, [2000; 3]));
+    set_draw_color(rgb(theme.header_text));
+    if is_finished {
+        draw_text(lang::strings().new_game_button, 475, 25);
+    } else {
+        draw_text(lang::strings().finish_button, 500, 25);
+    }
+}
+
+pub fn draw_replay_progress(step: usize, total: usize) {
+    let theme = theme::current();
+    draw_rect_fill(450,0,150, OFFSET_Y, rgb(theme.header_replay));
+    set_draw_color(rgb(theme.header_text));
+    draw_text(&format!("{}/{}", step, total), 480, 25);
+}
+
+pub fn draw_sidebar_header(title: &str) {
+    let x = sidebar_x();
+    let theme = theme::current();
+    draw_rect_fill(x, 0, SIDEBAR_WIDTH, CELL_SIZE, rgb(theme.sidebar_header));
+    set_draw_color(rgb(theme.header_text));
+    draw_text("≡", x + 15, 25);
+    draw_text(title, x + 80, 25);
+}
+
+// Значок звука в правом углу заголовка боковой панели, щелчок по нему выключает звук
+pub fn draw_sound_state(preferences: &Preferences) {
+    let x = sidebar_x() + SIDEBAR_WIDTH - SOUND_TOGGLE_WIDTH;
+    let theme = theme::current();
+
+    if preferences.muted || preferences.volume <= 0.0 {
+        draw_rect_fill(x, 0, SOUND_TOGGLE_WIDTH, CELL_SIZE, rgb(theme.warning));
+        set_draw_color(rgb(theme.header_text));
+        draw_text("♪×", x + 8, 25);
+    } else {
+        set_draw_color(rgb(theme.header_text));
+        draw_text("♪", x + 12, 25);
+    }
+}
+
+pub fn draw_sidebar_row(row: i32, text: &str) {
+    let x = sidebar_x();
+    let theme = theme::current();
+    let color = if row % 2 == 1 {
+        theme.sidebar_row
+    }  else {
+        theme.sidebar_row_alt
+    };
+    draw_rect_fill(x, CELL_SIZE * row, SIDEBAR_WIDTH, CELL_SIZE, rgb(color));
+    set_draw_color(rgb(theme.sidebar_text));
+    draw_text(text, x + 10, CELL_SIZE * row + 25);
+}
+
+pub fn draw_sidebar_row_details(row: i32, text: &str, details: &str) {
+    let x = sidebar_x();
+    let theme = theme::current();
+    draw_sidebar_row(row, "");
+    set_draw_color(rgb(theme.sidebar_text));
+    draw_text(text, x + 10, CELL_SIZE * row + 18);
+    set_draw_color(rgb(theme.sidebar_details));
+    draw_text(details, x + 10, CELL_SIZE * row + 35);
+}
+
+pub fn draw_leaders_table(leaders: &[&LeaderRecord], sort: LeadersSort, scroll: usize) {
+    draw_sidebar_header(lang::strings().leaders_title);
+
+    for row in 0..SIDEBAR_ROWS {
+        match leaders.get(scroll + row as usize) {
+            Some(record) => {
+                let player = if record.player.is_empty() {
+                    "-"
+                } else {
+                    record.player.as_str()
+                };
+
+                let marker = if record.replay.is_some() { "▶" } else { " " };
+
+                draw_sidebar_row_details(
+                    row + 1,
+                    &format!("{} {:<5} {}", marker, record.score, player),
+                    &format!("{:<14} {}", record.longest_word.to_uppercase(), sort.column(record))
+                );
+            },
+            None => draw_sidebar_row(row + 1, "-")
         }
     }
 }
 
+pub fn draw_leaders_toolbar(sort: LeadersSort, filter: &LeadersFilter) {
+    let x = sidebar_x();
+    let theme = theme::current();
+    draw_rect_fill(x, CELL_SIZE * 11, SIDEBAR_WIDTH, CELL_SIZE, rgb(theme.sidebar_toolbar));
+    set_draw_color(rgb(theme.header_text));
+    draw_text(&format!("▼ {}", sort.title()), x + 10, CELL_SIZE * 11 + 25);
+    draw_text(&format!("◆ {}", filter.title()), x + SIDEBAR_WIDTH / 2, CELL_SIZE * 11 + 25);
+}
+
+pub fn draw_words_stats(stats: Ref<WordStats>) {
+    draw_sidebar_header(lang::strings().stats_title);
+
+    let most_played = stats.most_played(STATS_ROWS as usize);
+    let least_played = stats.least_played(STATS_ROWS as usize);
+
+    for row in 0..STATS_ROWS {
+        let text = match most_played.get(row as usize) {
+            Some((word, count)) => format!("▲ {:<5} {}", count, word.to_uppercase()),
+            None => String::from("-")
+        };
+        draw_sidebar_row(row + 1, &text);
+
+        let text = match least_played.get(row as usize) {
+            Some((word, count)) => format!("▼ {:<5} {}", count, word.to_uppercase()),
+            None => String::from("-")
+        };
+        draw_sidebar_row(row + 1 + STATS_ROWS, &text);
+    }
+}
+
+pub fn draw_word_log(log: &[WordRecord], scroll: usize) {
+    draw_sidebar_header(lang::strings().word_log_title);
+
+    for row in 0..SIDEBAR_ROWS {
+        let idx = scroll + row as usize;
+
+        let text = match log.get(idx) {
+            Some(record) => format!(
+                "{:>2}. {:<11} {:>4} +{} +{}",
+                idx + 1,
+                record.word.to_uppercase(),
+                record.base_score,
+                record.bonus_score,
+                record.rarity_score
+            ),
+            None => String::from("-")
+        };
+
+        draw_sidebar_row(row + 1, &text);
+    }
+}
+
+pub fn draw_player_stats(name: &str, stats: Ref<PlayerStats>) {
+    draw_sidebar_header(lang::strings().player_title);
+
+    let strings = lang::strings();
+    let rows = [
+        format!("{}: {}", strings.player, name.to_uppercase()),
+        format!("{}: {}", strings.games_played, stats.games_played),
+        format!("{}: {}", strings.best_score, stats.best_score),
+        format!("{}: {}", strings.average_score, stats.average_score()),
+        format!("{}: {}", strings.best_word, stats.best_word.to_uppercase()),
+        format!("{}: {}", strings.total_score, stats.total_score),
+        format!("{}: {}", strings.total_words, stats.total_words)
+    ];
+
+    for row in 0..SIDEBAR_ROWS {
+        draw_sidebar_row(row + 1, rows.get(row as usize).map(|text| text.as_str()).unwrap_or(""));
+    }
+}
+
 pub fn draw_controls() {
-    let x = WIDTH * CELL_SIZE;
-    let color = enums::Color::rgb_color(50, 80, 130);
-    draw_rect_fill(x, CELL_SIZE * 11, SIDEBAR_WIDTH, CELL_SIZE, color);
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "УПРАВЛЕНИЕ"), WIDTH * CELL_SIZE + 100, CELL_SIZE * 11 + 25);
-
-    let color = enums::Color::rgb_color(50, 60, 70);
-    draw_rect_fill(x, CELL_SIZE * 12, SIDEBAR_WIDTH, CELL_SIZE, color);
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "ЛЕВАЯ КН. МЫШИ - ВЫДЕЛЕНИЕ"), WIDTH * CELL_SIZE + 10, CELL_SIZE * 12 + 25);
-
-    let color = enums::Color::rgb_color(40, 50, 60);
-    draw_rect_fill(x, CELL_SIZE * 13, SIDEBAR_WIDTH, CELL_SIZE, color);
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "ПРАВАЯ КН. МЫШИ - ОТМЕНА"), WIDTH * CELL_SIZE + 10, CELL_SIZE * 13 + 25);
-
-    let color = enums::Color::rgb_color(50, 60, 70);
-    draw_rect_fill(x, CELL_SIZE * 14, SIDEBAR_WIDTH, CELL_SIZE, color);
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "ПОВТОРНЫЙ КЛИК - ВЫБОР"), WIDTH * CELL_SIZE + 10, CELL_SIZE * 14 + 25);
-
-    let color = enums::Color::rgb_color(40, 50, 60);
-    draw_rect_fill(x, CELL_SIZE * 15, SIDEBAR_WIDTH, CELL_SIZE, color);
-    set_draw_color(enums::Color::rgb_color(255,255,255));
-    draw_text( &format!("{}", "ФИНИШ - ЗАКОНЧИТЬ ИГРУ"), WIDTH * CELL_SIZE + 10, CELL_SIZE * 15 + 25);
-}
-
-pub fn draw_bg() {
-    let red = animation::ColorGenerator::get_color_component(
-        500, 2000,60, 80
-    );
-    let green = animation::ColorGenerator::get_color_component(
-        250, 6000,80, 100
-    );
-    let blue = animation::ColorGenerator::get_color_component(
-        1000, 3000,100, 120
-    );
+    let x = sidebar_x();
+    let strings = lang::strings();
+    let (select, accept) = match preferences::current().selection_mode {
+        SelectionMode::Click => (strings.controls_click, strings.controls_accept_click),
+        SelectionMode::Drag => (strings.controls_drag, strings.controls_accept_drag)
+    };
+
+    let theme = theme::current();
+    draw_rect_fill(x, CELL_SIZE * 11, SIDEBAR_WIDTH, CELL_SIZE, rgb(theme.sidebar_toolbar));
+    set_draw_color(rgb(theme.header_text));
+    draw_text(strings.controls_title, x + 100, CELL_SIZE * 11 + 25);
+
+    let rows = [select, strings.controls_cancel, accept, strings.controls_finish];
+
+    for (idx, text) in rows.iter().enumerate() {
+        let row = 12 + idx as i32;
+        let color = if idx % 2 == 0 { theme.controls_row } else { theme.controls_row_alt };
+        draw_rect_fill(x, CELL_SIZE * row, SIDEBAR_WIDTH, CELL_SIZE, rgb(color));
+        set_draw_color(rgb(theme.sidebar_text));
+        draw_text(text, x + 10, CELL_SIZE * row + 25);
+    }
+}
+
+pub fn draw_notice(text: &str) {
+    let x = sidebar_x();
+    let theme = theme::current();
+    draw_rect_fill(x, CELL_SIZE * 15, SIDEBAR_WIDTH, CELL_SIZE, rgb(theme.warning));
+    set_draw_color(rgb(theme.header_text));
+    draw_text(text, x + 10, CELL_SIZE * 15 + 25);
+}
+
+pub fn draw_bg(width: i32, height: i32) {
     draw_rect_fill(
         0,
         OFFSET_Y,
-        WIDTH * CELL_SIZE,
-        HEIGHT * CELL_SIZE,
-        enums::Color::rgb_color(red,green, blue)
+        width,
+        height,
+        pulse(&theme::current().background, 500, [2000, 6000, 3000])
     );
-}
\ No newline at end of file
+}
+
+// Поле с выделением. Пока уходит принятое слово, поверх нового состояния поля
+// показываются гаснущие клетки и падающие на их место верхние, а поверх всего - эффекты
+pub fn draw_field(field: &Field, view: &BoardView) {
+    let is_word = field.is_word();
+    let is_bonus_exists = field.is_bonus_exists();
+    let (last_checked, _, _) = field.find_max();
+    let theme = theme::current();
+    let (area_width, area_height) = board_area(field.get_width(), field.get_height());
+    let highlight = view.highlight(is_word);
+    let clearing = view.clearing.borrow();
+    let falls = clearing.as_ref().map_or(&[][..], |view| &view.clearing.falls[..]);
+
+    draw_bg(area_width, area_height);
+
+    for i in 0..field.get_height() {
+        for j in 0..field.get_width() {
+            let offset = ((j + i * field.get_width()) * 225) as u32;
+            let color = if is_word {
+                mix(theme.selection, pulse(&theme.valid_selection, offset, [4000; 3]).to_rgb(), highlight)
+            } else {
+                rgb(theme.selection)
+            };
+            let bonus_color = pulse(&theme.bonus, offset, [2000, 3222, 1000]);
+            let selection_letter = rgb(theme.selection_letter);
+            let gray_color = rgb(theme.tile);
+            let almost_white = rgb(theme.letter);
+
+            // Сюда ещё только падает клетка сверху
+            if falls.iter().any(|fall| fall.x == j && fall.to_y == i) {
+                draw_empty_ceil(j, i, gray_color);
+                continue;
+            }
+
+            let checked_value = field.is_checked(j, i);
+            let is_on_the_bonus_line = field.is_on_the_bonus_line(j,i);
+            let letter = field.get(j,i).letter;
+
+            if checked_value > 0 {
+
+                if is_word && is_bonus_exists {
+                    draw_ceil(j,i,bonus_color, selection_letter, letter);
+                    draw_bonus_hatch(j, i, rgb(theme.hatch));
+                } else {
+                    draw_ceil(j,i,color,selection_letter, letter);
+                }
+
+                draw_ceil_direction(j,i,
+                                    field.get_before_direction(j,i),
+                                    field.get_direction(j,i)
+                );
+
+                if is_word {
+                    draw_word_outline(j, i, rgb(theme.outline));
+
+                    if checked_value == last_checked {
+                        draw_checkmark(j, i, rgb(theme.outline));
+                    }
+                }
+
+            } else {
+                match field.get(j,i).ceil_type {
+                    CeilType::Active => {
+                        if is_word && is_bonus_exists && is_on_the_bonus_line {
+                            draw_ceil(j, i, bonus_color, almost_white, letter);
+                            draw_bonus_hatch(j, i, rgb(theme.hatch));
+                        } else {
+                            draw_ceil(j, i, gray_color, almost_white, letter);
+                        }
+                    },
+                    CeilType::Empty => {
+                        if is_word && is_bonus_exists && is_on_the_bonus_line {
+                            draw_empty_ceil(j, i, bonus_color);
+                            draw_bonus_hatch(j, i, rgb(theme.hatch));
+                        } else {
+                            draw_empty_ceil(j, i, gray_color);
+                        }
+                    },
+                    CeilType::Bonus => {
+                        if is_word && field.is_bonus(j, i) {
+                            draw_ceil(
+                                j,
+                                i,
+                                bonus_color,
+                                pulse(&theme.bonus_mark, offset, [4000; 3]),
+                                '!'
+                            );
+                            draw_bonus_hatch(j, i, rgb(theme.hatch));
+                        } else {
+                            draw_empty_ceil(j,i, rgb(theme.bonus_tile));
+                        }
+                    }
+                }
+
+            }
+        }
+    }
+
+    if let Some(view) = clearing.as_ref() {
+        let fade = 1.0 - view.fade.value();
+
+        if fade > 0.0 {
+            for cleared in &view.clearing.removed {
+                let bg = mix(theme.tile, theme.fading_tile, fade);
+                let fg = mix(theme.fading_letter, theme.letter, 1.0 - fade);
+                draw_ceil(cleared.x, cleared.y, bg, fg, cleared.ceil.letter);
+            }
+        }
+
+        let progress = view.fall.value();
+
+        for fall in falls {
+            let ceil = field.get(fall.x, fall.to_y);
+            let row = fall.from_y as f32 + (fall.to_y - fall.from_y) as f32 * progress;
+            let top = OFFSET_Y + (row * CELL_SIZE as f32).round() as i32;
+
+            match ceil.ceil_type {
+                CeilType::Bonus => draw_tile(fall.x * CELL_SIZE, top, rgb(theme.bonus_tile), None),
+                _ => draw_tile(fall.x * CELL_SIZE, top, rgb(theme.tile), Some((rgb(theme.letter), ceil.letter)))
+            }
+        }
+    }
+
+    drop(clearing);
+    view.effects.borrow_mut().draw(area_width, area_height);
+}
+
+pub fn popup_menu(items: &[String]) -> Option<usize> {
+    let mut menu = menu::MenuButton::default();
+    menu.set_type(menu::MenuButtonType::Popup3);
+
+    for item in items {
+        menu.add_choice(item);
+    }
+
+    let chosen = menu.popup().map(|_| menu.value());
+
+    menu::MenuButton::delete(menu);
+
+    match chosen {
+        Some(idx) if idx >= 0 => Some(idx as usize),
+        _ => None
+    }
+}
+
+pub fn choose_file(title: &str) -> Option<PathBuf> {
+    let mut chooser = dialog::NativeFileChooser::new(dialog::NativeFileChooserType::BrowseFile);
+    chooser.set_title(title);
+    chooser.show();
+
+    let filename = chooser.filename();
+
+    if filename.as_os_str().is_empty() {
+        None
+    } else {
+        Some(filename)
+    }
+}
+
+pub fn choose_save_file(title: &str, preset: &str) -> Option<PathBuf> {
+    let mut chooser = dialog::NativeFileChooser::new(dialog::NativeFileChooserType::BrowseSaveFile);
+    chooser.set_title(title);
+    chooser.set_filter("*.ron");
+    chooser.set_preset_file(preset);
+    chooser.set_option(dialog::NativeFileChooserOptions::SaveAsConfirm);
+    chooser.show();
+
+    let filename = chooser.filename();
+
+    if filename.as_os_str().is_empty() {
+        None
+    } else {
+        Some(filename)
+    }
+}
+
+pub struct GameSummary {
+    pub score: i32,
+    pub words: usize,
+    pub longest_word: String,
+    pub rank: Option<usize>,
+    pub missed: Vec<String>
+}
+
+pub fn show_report(title: &str, lines: &[String]) {
+    let mut wind = window::Window::default()
+        .with_size(500, 420)
+        .with_label(title);
+    wind.make_modal(true);
+
+    let mut browser = browser::HoldBrowser::new(10, 10, 480, 350, None);
+    for line in lines {
+        browser.add(line);
+    }
+
+    let mut close = button::Button::new(320, 375, 170, 35, lang::strings().close);
+    close.set_callback(|button| {
+        if let Some(mut wind) = button.window() {
+            wind.hide();
+        }
+    });
+
+    wind.end();
+    wind.show();
+
+    while wind.shown() {
+        app::wait();
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum SummaryAction {
+    NewGame,
+    ReviewBoard
+}
+
+pub fn show_summary(summary: &GameSummary) -> SummaryAction {
+    let mut wind = window::Window::default()
+        .with_size(400, 470)
+        .with_label(lang::strings().summary_title);
+    wind.make_modal(true);
+
+    let rank = match summary.rank {
+        Some(rank) => format!("{}", rank + 1),
+        None => String::from("-")
+    };
+    let longest_word = if summary.longest_word.is_empty() {
+        String::from("-")
+    } else {
+        summary.longest_word.to_uppercase()
+    };
+
+    let mut frame = frame::Frame::new(20, 10, 360, 110, None);
+    let strings = lang::strings();
+    frame.set_label(&format!(
+        "{}: {}\n{}: {}\n{}: {}\n{}: {}",
+        strings.summary_score,
+        summary.score,
+        strings.summary_words,
+        summary.words,
+        strings.summary_longest_word,
+        longest_word,
+        strings.summary_rank,
+        rank
+    ));
+    frame.set_align(enums::Align::Left | enums::Align::Inside);
+
+    let mut browser = browser::HoldBrowser::new(20, 150, 360, 250, None);
+    browser.set_label(&format!("{}: {}", strings.missed_words, summary.missed.len()));
+    browser.set_align(enums::Align::TopLeft);
+    for word in &summary.missed {
+        browser.add(&word.to_uppercase());
+    }
+
+    let action = Rc::new(Cell::new(SummaryAction::ReviewBoard));
+
+    let mut new_game = button::Button::new(20, 420, 170, 35, strings.new_game);
+    new_game.set_callback({
+        let action = Rc::clone(&action);
+        move |button| {
+            action.set(SummaryAction::NewGame);
+            if let Some(mut wind) = button.window() {
+                wind.hide();
+            }
+        }
+    });
+
+    let mut review = button::Button::new(210, 420, 170, 35, strings.review_board);
+    review.set_callback({
+        let action = Rc::clone(&action);
+        move |button| {
+            action.set(SummaryAction::ReviewBoard);
+            if let Some(mut wind) = button.window() {
+                wind.hide();
+            }
+        }
+    });
+
+    wind.end();
+    wind.show();
+
+    while wind.shown() {
+        app::wait();
+    }
+
+    action.get()
+}
+
+// Просмотр записанной игры: по шагам или с исходными паузами между действиями
+pub fn show_replay(replay: &Replay, start: Field, title: &str) {
+    let strings = lang::strings();
+    let (board_width, board_height) = board_area(start.get_width(), start.get_height());
+    let board_height = board_height + OFFSET_Y;
+    let mut wind = window::Window::default()
+        .with_size(board_width, board_height + 50)
+        .with_label(title);
+    wind.make_modal(true);
+
+    let field = Rc::new(start.clone());
+    let view = Rc::new(BoardView::default());
+    let position = Rc::new(Cell::new(0usize));
+    let is_playing = Rc::new(Cell::new(false));
+    let total = replay.steps.len();
+
+    let step = {
+        let field = Rc::clone(&field);
+        let position = Rc::clone(&position);
+        let replay = replay.clone();
+        move || {
+            if let Some(step) = replay.steps.get(position.get()) {
+                let outcome = replay.apply(&field, step);
+                match step.action {
+                    Move::Deselect => sound::play_undo(),
+                    Move::Select(_, _) => sound::play_outcome(&outcome, &field)
+                }
+                position.set(position.get() + 1);
+            }
+        }
+    };
+
+    let mut restart = button::Button::new(10, board_height + 8, 135, 35, strings.replay_restart);
+    restart.set_callback({
+        let field = Rc::clone(&field);
+        let view = Rc::clone(&view);
+        let position = Rc::clone(&position);
+        move |_| {
+            field.assign(&start);
+            view.reset();
+            position.set(0);
+        }
+    });
+
+    let mut next = button::Button::new(155, board_height + 8, 135, 35, strings.replay_step);
+    next.set_callback({
+        let step = step.clone();
+        let is_playing = Rc::clone(&is_playing);
+        move |_| {
+            is_playing.set(false);
+            step();
+        }
+    });
+
+    let mut play = button::Button::new(300, board_height + 8, 135, 35, strings.replay_play);
+    play.set_callback({
+        let is_playing = Rc::clone(&is_playing);
+        move |_| is_playing.set(!is_playing.get())
+    });
+
+    let mut close = button::Button::new(445, board_height + 8, 135, 35, strings.close);
+    close.set_callback(|button| {
+        if let Some(mut wind) = button.window() {
+            wind.hide();
+        }
+    });
+
+    wind.draw({
+        let field = Rc::clone(&field);
+        let view = Rc::clone(&view);
+        let position = Rc::clone(&position);
+        move |_| {
+            view.sync(&field);
+            draw_scores(field.get_scores(), board_width, view.flash());
+            draw_longest_word(field.get_longest_word());
+            draw_replay_progress(position.get(), total);
+            draw_field(&field, &view);
+        }
+    });
+
+    wind.end();
+    wind.show();
+
+    let mut last_step = Instant::now();
+    let mut drawn_position = position.get();
+    let mut last_pulse = Instant::now();
+
+    while wind.shown() {
+        app::wait_for(0.01).ok();
+
+        if is_playing.get() && !view.is_clearing() {
+            match replay.steps.get(position.get()) {
+                Some(current) => {
+                    let previous = position.get()
+                        .checked_sub(1)
+                        .map_or(current.time, |idx| replay.steps[idx].time);
+                    let delay = current.time.saturating_sub(previous)
+                        .clamp(REPLAY_MIN_DELAY_MS, REPLAY_MAX_DELAY_MS);
+
+                    if last_step.elapsed() >= Duration::from_millis(delay) {
+                        step();
+                        last_step = Instant::now();
+                    }
+                },
+                None => is_playing.set(false)
+            }
+        }
+
+        play.set_label(if is_playing.get() { strings.replay_pause } else { strings.replay_play });
+
+        let is_pulse_due = is_pulsing(&field) && last_pulse.elapsed() >= Duration::from_secs_f64(PULSE_INTERVAL);
+
+        if is_animating() || is_pulse_due || position.get() != drawn_position {
+            drawn_position = position.get();
+            last_pulse = Instant::now();
+            wind.redraw();
+        }
+    }
+}
+
+fn preferences_row(row: i32) -> i32 {
+    15 + row * 45
+}
+
+fn preferences_label(row: i32, text: &str) {
+    frame::Frame::new(20, preferences_row(row), 170, 30, None)
+        .with_label(text)
+        .with_align(enums::Align::Left | enums::Align::Inside);
+}
+
+fn choice<T: Copy + PartialEq>(row: i32, label: &str, items: &[T], title: fn(&T) -> &'static str, value: T) -> menu::Choice {
+    preferences_label(row, label);
+    let mut choice = menu::Choice::new(200, preferences_row(row), 260, 30, None);
+
+    for item in items {
+        choice.add_choice(title(item));
+    }
+    choice.set_value(items.iter().position(|item| *item == value).unwrap_or(0) as i32);
+
+    choice
+}
+
+// Место темы в списке: сначала встроенные темы, за ними - файлы из каталога тем
+fn theme_position(user_themes: &[String], name: ThemeName, file: Option<&String>) -> i32 {
+    match name {
+        ThemeName::User => file
+            .and_then(|file| user_themes.iter().position(|theme| theme == file))
+            .map_or(0, |idx| (ThemeName::PRESETS.len() + idx) as i32),
+        preset => ThemeName::PRESETS.iter().position(|item| *item == preset).unwrap_or(0) as i32
+    }
+}
+
+// Всё, что меняется в окне настроек
+#[derive(Clone)]
+pub struct PreferencesForm {
+    pub preferences: Preferences,
+    pub scoring_profile: ScoringProfile,
+    pub sound_pack: Option<String>,
+    pub user_theme: Option<String>
+}
+
+// Окно настроек. Изменения применяются сразу, а при отмене возвращаются прежние значения
+pub fn show_preferences(form: PreferencesForm) -> Option<PreferencesForm> {
+    let PreferencesForm { preferences, scoring_profile, sound_pack, user_theme } = form;
+    let original_theme = theme::current();
+    let strings = lang::strings();
+    let mut wind = window::Window::default()
+        .with_size(480, preferences_row(12) + 50)
+        .with_label(strings.preferences_title);
+    wind.make_modal(true);
+
+    let current = Rc::new(Cell::new(preferences));
+    let scoring = Rc::new(Cell::new(scoring_profile));
+    let pack = Rc::new(RefCell::new(sound_pack.clone()));
+    let chosen_theme = Rc::new(RefCell::new(user_theme.clone()));
+    let is_confirmed = Rc::new(Cell::new(false));
+
+    let update = {
+        let current = Rc::clone(&current);
+        move |change: &dyn Fn(&mut Preferences)| {
+            let mut preferences = current.get();
+            change(&mut preferences);
+            preferences.apply();
+            current.set(preferences);
+        }
+    };
+
+    preferences_label(0, strings.volume);
+    let mut volume = valuator::HorNiceSlider::new(200, preferences_row(0), 260, 30, None);
+    volume.set_range(0.0, 1.0);
+    volume.set_value(preferences.volume as f64);
+    volume.set_callback({
+        let update = update.clone();
+        move |slider| {
+            let value = slider.value() as f32;
+            update(&|preferences| preferences.volume = value);
+        }
+    });
+
+    let mut muted = button::CheckButton::new(200, preferences_row(1), 260, 30, strings.mute);
+    muted.set_checked(preferences.muted);
+    muted.set_callback({
+        let update = update.clone();
+        move |button| {
+            let value = button.is_checked();
+            update(&|preferences| preferences.muted = value);
+        }
+    });
+
+    preferences_label(2, strings.sound_events);
+    for (idx, sound) in sound::Sound::ALL.iter().copied().enumerate() {
+        let (row, column) = (2 + idx as i32 / 3, idx as i32 % 3);
+        let mut event = button::CheckButton::new(200 + column * 87, preferences_row(row), 85, 30, sound.title());
+        event.set_checked(preferences.sound_events.is_enabled(sound));
+        event.set_callback({
+            let update = update.clone();
+            move |button| {
+                let value = button.is_checked();
+                update(&|preferences| preferences.sound_events.set_enabled(sound, value));
+            }
+        });
+    }
+
+    // Первый пункт - встроенные звуки, остальные - наборы из каталога наборов
+    let packs = sound::packs();
+    preferences_label(4, strings.sound_pack);
+    let mut pack_choice = menu::Choice::new(200, preferences_row(4), 260, 30, None);
+    pack_choice.add_choice(strings.builtin_sound_pack);
+    for name in &packs {
+        pack_choice.add_choice(name);
+    }
+    pack_choice.set_value(
+        sound_pack.as_ref()
+            .and_then(|name| packs.iter().position(|pack| pack == name))
+            .map_or(0, |idx| idx as i32 + 1)
+    );
+    pack_choice.set_callback({
+        let pack = Rc::clone(&pack);
+        move |choice| {
+            let chosen = (choice.value() as usize).checked_sub(1).and_then(|idx| packs.get(idx)).cloned();
+
+            match sound::set_pack(chosen.as_deref()) {
+                Ok(()) => *pack.borrow_mut() = chosen,
+                Err(err) => {
+                    dialog::alert_default(&format!("{}: {}", lang::strings().cannot_load_sound_pack, err));
+                    let _ = sound::set_pack(pack.borrow().as_deref());
+                    let previous = pack.borrow().as_ref()
+                        .and_then(|name| packs.iter().position(|pack| pack == name))
+                        .map_or(0, |idx| idx as i32 + 1);
+                    choice.set_value(previous);
+                }
+            }
+        }
+    });
+
+    preferences_label(5, strings.animation_speed);
+    let mut speed = valuator::HorNiceSlider::new(200, preferences_row(5), 260, 30, None);
+    speed.set_range(MIN_ANIMATION_SPEED as f64, MAX_ANIMATION_SPEED as f64);
+    speed.set_value(preferences.animation_speed as f64);
+    speed.set_callback({
+        let update = update.clone();
+        move |slider| {
+            let value = slider.value() as f32;
+            update(&|preferences| preferences.animation_speed = value);
+        }
+    });
+
+    let mut reduced_motion = button::CheckButton::new(200, preferences_row(6), 260, 30, strings.reduced_motion);
+    reduced_motion.set_checked(preferences.reduced_motion);
+    reduced_motion.set_callback({
+        let update = update.clone();
+        move |button| {
+            let value = button.is_checked();
+            update(&|preferences| preferences.reduced_motion = value);
+        }
+    });
+
+    let user_themes = theme::user_themes();
+    preferences_label(7, strings.theme);
+    let mut theme_choice = menu::Choice::new(200, preferences_row(7), 260, 30, None);
+    for preset in ThemeName::PRESETS {
+        theme_choice.add_choice(preset.title());
+    }
+    for name in &user_themes {
+        theme_choice.add_choice(name);
+    }
+    theme_choice.set_value(theme_position(&user_themes, preferences.theme, user_theme.as_ref()));
+    theme_choice.set_callback({
+        let update = update.clone();
+        let current = Rc::clone(&current);
+        let chosen_theme = Rc::clone(&chosen_theme);
+        let user_themes = user_themes.clone();
+        move |choice| {
+            let idx = choice.value().max(0) as usize;
+            let (name, file) = match ThemeName::PRESETS.get(idx) {
+                Some(preset) => (*preset, None),
+                None => (ThemeName::User, user_themes.get(idx - ThemeName::PRESETS.len()).cloned())
+            };
+
+            match theme::load(name, file.as_deref()) {
+                Ok(loaded) => {
+                    theme::set(loaded);
+                    *chosen_theme.borrow_mut() = file;
+                    update(&|preferences| preferences.theme = name);
+                },
+                Err(err) => {
+                    dialog::alert_default(&format!("{}: {}", lang::strings().cannot_load_theme, err));
+                    let previous = chosen_theme.borrow();
+                    choice.set_value(theme_position(&user_themes, current.get().theme, previous.as_ref()));
+                }
+            }
+        }
+    });
+
+    let mut selection = choice(8, strings.selection_mode, &SelectionMode::ALL, SelectionMode::title, preferences.selection_mode);
+    selection.set_callback({
+        let update = update.clone();
+        move |choice| {
+            let value = SelectionMode::ALL[choice.value().max(0) as usize];
+            update(&|preferences| preferences.selection_mode = value);
+        }
+    });
+
+    let mut scoring_choice = choice(9, strings.scoring_profile, &ScoringProfile::ALL, ScoringProfile::title, scoring_profile);
+    scoring_choice.set_callback({
+        let scoring = Rc::clone(&scoring);
+        move |choice| scoring.set(ScoringProfile::ALL[choice.value().max(0) as usize])
+    });
+
+    preferences_label(10, strings.board_size);
+    let mut board_width = misc::Spinner::new(200, preferences_row(10), 125, 30, None);
+    let mut board_height = misc::Spinner::new(335, preferences_row(10), 125, 30, None);
+    for (spinner, value) in [(&mut board_width, preferences.board_width), (&mut board_height, preferences.board_height)] {
+        spinner.set_range(MIN_BOARD_SIZE as f64, MAX_BOARD_SIZE as f64);
+        spinner.set_step(1.0);
+        spinner.set_value(value as f64);
+    }
+    board_width.set_callback({
+        let update = update.clone();
+        move |spinner| {
+            let value = spinner.value() as i32;
+            update(&|preferences| preferences.board_width = value);
+        }
+    });
+    board_height.set_callback({
+        let update = update.clone();
+        move |spinner| {
+            let value = spinner.value() as i32;
+            update(&|preferences| preferences.board_height = value);
+        }
+    });
+
+    let mut language = choice(11, strings.language, &Language::ALL, Language::title, preferences.language);
+    language.set_callback({
+        let update = update.clone();
+        move |choice| {
+            let value = Language::ALL[choice.value().max(0) as usize];
+            update(&|preferences| preferences.language = value);
+        }
+    });
+
+    let mut cancel = button::Button::new(20, preferences_row(12), 210, 35, strings.cancel);
+    cancel.set_callback(|button| {
+        if let Some(mut wind) = button.window() {
+            wind.hide();
+        }
+    });
+
+    let mut ok = button::Button::new(250, preferences_row(12), 210, 35, strings.ok);
+    ok.set_callback({
+        let is_confirmed = Rc::clone(&is_confirmed);
+        move |button| {
+            is_confirmed.set(true);
+            if let Some(mut wind) = button.window() {
+                wind.hide();
+            }
+        }
+    });
+
+    wind.end();
+    wind.show();
+
+    while wind.shown() {
+        app::wait();
+    }
+
+    if is_confirmed.get() {
+        Some(PreferencesForm {
+            preferences: current.get().normalized(),
+            scoring_profile: scoring.get(),
+            sound_pack: pack.take(),
+            user_theme: chosen_theme.take()
+        })
+    } else {
+        preferences.apply();
+        theme::set(original_theme);
+        let _ = sound::set_pack(sound_pack.as_deref());
+        None
+    }
+}
@@ -0,0 +1,204 @@
+use crate::application::{lang, save, settings};
+use crate::field::*;
+use crate::leaders::*;
+use crate::stats::ScoringProfile;
+
+pub enum Verdict {
+    Verified,
+    // Записи нет, например у результатов из старых версий игры, или её не проверить до конца
+    Unverifiable(&'static str),
+    Tampered(String)
+}
+
+impl Verdict {
+    pub fn title(&self) -> String {
+        match self {
+            Verdict::Verified => String::from(lang::strings().verified),
+            Verdict::Unverifiable(reason) => String::from(*reason),
+            Verdict::Tampered(reason) => format!("{}: {}", lang::strings().tampered, reason)
+        }
+    }
+}
+
+// Результат проверяется повторным проигрыванием записанных ходов по правилам игры
+pub fn verify(record: &LeaderRecord) -> Verdict {
+    let replay = match &record.replay {
+        Some(replay) => replay,
+        None => return Verdict::Unverifiable(lang::strings().unverifiable)
+    };
+
+    let field = match replay.to_field() {
+        Ok(field) => field,
+        Err(err) => return Verdict::Tampered(format!("{} ({})", lang::strings().broken_replay, err))
+    };
+
+    // Без зерна начальное поле могло быть любым, в том числе подобранным
+    if record.seed == 0 {
+        return Verdict::Tampered(String::from(lang::strings().missing_seed));
+    }
+
+    let generated = Field::new(replay.width, replay.height);
+    generated.generate_with_seed(record.seed);
+
+    if save::encode_board(&generated.get_board()) != (replay.letters.clone(), replay.tiles.clone()) {
+        return Verdict::Tampered(String::from(lang::strings().seed_mismatch));
+    }
+
+    // В классическом подсчёте за редкость очков не дают
+    let rarity_limit = match record.scoring_profile {
+        ScoringProfile::Classic => 0,
+        ScoringProfile::Rarity => settings::SCORES_FOR_RARE_WORD
+    };
+
+    for step in &replay.steps {
+        replay.apply(&field, step);
+    }
+
+    let played = field.get_word_log();
+
+    if played.len() != replay.words.len() || played.len() != record.words {
+        return Verdict::Tampered(String::from(lang::strings().words_mismatch));
+    }
+
+    for (played, recorded) in played.iter().zip(replay.words.iter()) {
+        if played.word != recorded.word || played.base_score != recorded.base_score ||
+            played.bonus_score != recorded.bonus_score {
+            return Verdict::Tampered(format!("{} {}", lang::strings().word_mismatch, recorded.word));
+        }
+
+        if !(0..=rarity_limit).contains(&recorded.rarity_score) {
+            return Verdict::Tampered(format!("{} {}", lang::strings().rarity_mismatch, recorded.word));
+        }
+    }
+
+    if field.get_scores() != record.score {
+        return Verdict::Tampered(format!("{} {}", field.get_scores(), lang::strings().score_mismatch));
+    }
+
+    // Очки за редкость зависят от корпуса и сыгранных раньше слов, которых в записи нет,
+    // поэтому пересчитать их нельзя
+    if played.iter().any(|word| word.rarity_score > 0) {
+        return Verdict::Unverifiable(lang::strings().unverifiable_rarity);
+    }
+
+    Verdict::Verified
+}
+
+// Строки отчёта по всей таблице и число записей, не прошедших проверку
+pub fn report(leaders: &[LeaderRecord]) -> (Vec<String>, usize) {
+    let mut tampered = 0;
+
+    let lines = leaders.iter()
+        .enumerate()
+        .map(|(idx, record)| {
+            let verdict = verify(record);
+
+            if let Verdict::Tampered(_) = verdict {
+                tampered += 1;
+            }
+
+            format!("{}. {} {} - {}", idx + 1, record.score, record.player, verdict.title())
+        })
+        .collect();
+
+    (lines, tampered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dictionary;
+
+    // Первое слово на поле в виде пути по клеткам
+    fn find_word(field: &Field, path: &mut Vec<(i32, i32)>) -> bool {
+        let word: String = path.iter().map(|&(x, y)| field.get(x, y).letter).collect();
+
+        if dictionary::get().is_word(&word) {
+            return true;
+        }
+
+        if !path.is_empty() && !dictionary::get().is_prefix(&word) {
+            return false;
+        }
+
+        for y in 0..field.get_height() {
+            for x in 0..field.get_width() {
+                let is_near = path.last().is_none_or(|&(px, py)| (px - x).abs() <= 1 && (py - y).abs() <= 1);
+
+                if !is_near || path.contains(&(x, y)) || !matches!(field.get(x, y).ceil_type, CeilType::Active) {
+                    continue;
+                }
+
+                path.push((x, y));
+
+                if find_word(field, path) {
+                    return true;
+                }
+
+                path.pop();
+            }
+        }
+
+        false
+    }
+
+    fn played_record(scoring_profile: ScoringProfile) -> LeaderRecord {
+        let field = Field::new(8, 8);
+        field.generate_with_seed(42);
+
+        let mut path = vec![];
+        assert!(find_word(&field, &mut path));
+
+        for &(x, y) in &path {
+            field.try_check(x, y);
+        }
+
+        let (x, y) = path[path.len() - 1];
+        assert!(matches!(field.try_check(x, y), CheckOutcome::Accepted(_)));
+
+        LeaderRecord::from_field(&field, "Игрок", scoring_profile)
+    }
+
+    fn is_tampered(record: &LeaderRecord) -> bool {
+        matches!(verify(record), Verdict::Tampered(_))
+    }
+
+    #[test]
+    fn verifies_honest_record() {
+        assert!(matches!(verify(&played_record(ScoringProfile::Classic)), Verdict::Verified));
+    }
+
+    #[test]
+    fn rejects_edited_score() {
+        let mut record = played_record(ScoringProfile::Classic);
+        record.score += 10;
+
+        assert!(is_tampered(&record));
+    }
+
+    #[test]
+    fn rejects_classic_rarity() {
+        let mut record = played_record(ScoringProfile::Classic);
+        record.score += settings::SCORES_FOR_RARE_WORD;
+        record.replay.as_mut().unwrap().words[0].rarity_score = settings::SCORES_FOR_RARE_WORD;
+
+        assert!(is_tampered(&record));
+    }
+
+    #[test]
+    fn flags_rarity_score() {
+        let mut record = played_record(ScoringProfile::Rarity);
+        record.score += settings::SCORES_FOR_RARE_WORD;
+        record.replay.as_mut().unwrap().words[0].rarity_score = settings::SCORES_FOR_RARE_WORD;
+
+        assert!(matches!(verify(&record), Verdict::Unverifiable(_)));
+    }
+
+    #[test]
+    fn rejects_zeroed_seed() {
+        let mut record = played_record(ScoringProfile::Classic);
+        record.seed = 0;
+
+        assert!(is_tampered(&record));
+    }
+}
@@ -2,6 +2,8 @@ mod application;
 
 use application::*;
 
-fn main() -> Result<(), confy::ConfyError> {
-    app()
-}
\ No newline at end of file
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    std::process::exit(cli::run(&args));
+}